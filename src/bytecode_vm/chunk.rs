@@ -1,22 +1,142 @@
-use std::ops::{Deref, DerefMut};
+use super::value::Value;
 
-pub enum Opcode<T> {
-    Value(T),
-    OpReturn, // Return from the current function
+/// A single bytecode instruction. Multi-byte operands (constant-pool
+/// indices, jump offsets, stack slots) are encoded as their own bytes
+/// immediately following the opcode byte in `Chunk::code`, matching how
+/// `Vm::run` and `Compiler` agree to read/write them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OpCode {
+    /// Pushes `constants[operand: u8]`.
+    Constant,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Negate,
+    Not,
+    Equal,
+    Less,
+    Greater,
+    Print,
+    /// Discards the top of the stack, e.g. after an expression statement.
+    Pop,
+    /// Binds the top of the stack to a global name, `names[operand: u8]`.
+    DefineGlobal,
+    GetGlobal,
+    SetGlobal,
+    /// Reads/writes `stack[frame_base + operand: u8]`.
+    GetLocal,
+    SetLocal,
+    /// Unconditional jump; operand is a `u16` forward offset from the byte
+    /// after the operand.
+    Jump,
+    /// Jump taken only when the top of the stack is falsy; the condition
+    /// is left on the stack either way (callers `Pop` it themselves, so
+    /// `and`/`or` short-circuiting can leave it as the expression result).
+    JumpIfFalse,
+    /// Unconditional jump backward; operand is a `u16` distance to
+    /// subtract from the ip after the operand, used to close `while`/
+    /// `loop`/`do while` bodies.
+    Loop,
+    /// Calls the callable `operand: u8` slots below the top of the stack,
+    /// which is itself `operand` arguments deep.
+    Call,
+    Return,
+    Pow,
+    BitAnd,
+    BitOr,
 }
 
-pub struct Chunk<T>(Vec<Opcode<T>>);
+impl OpCode {
+    pub fn from_u8(byte: u8) -> Self {
+        match byte {
+            0 => Self::Constant,
+            1 => Self::Add,
+            2 => Self::Sub,
+            3 => Self::Mul,
+            4 => Self::Div,
+            5 => Self::Mod,
+            6 => Self::Negate,
+            7 => Self::Not,
+            8 => Self::Equal,
+            9 => Self::Less,
+            10 => Self::Greater,
+            11 => Self::Print,
+            12 => Self::Pop,
+            13 => Self::DefineGlobal,
+            14 => Self::GetGlobal,
+            15 => Self::SetGlobal,
+            16 => Self::GetLocal,
+            17 => Self::SetLocal,
+            18 => Self::Jump,
+            19 => Self::JumpIfFalse,
+            20 => Self::Loop,
+            21 => Self::Call,
+            22 => Self::Return,
+            23 => Self::Pow,
+            24 => Self::BitAnd,
+            25 => Self::BitOr,
+            _ => unreachable!("corrupt bytecode: no opcode for byte {byte}"),
+        }
+    }
+}
 
-impl<T> Deref for Chunk<T> {
-    type Target = Vec<Opcode<T>>;
+/// A compiled instruction stream: the raw bytes, the constants they
+/// reference by index, and a source line per byte so a `Vm` runtime error
+/// can report where it happened.
+#[derive(Debug, Default)]
+pub struct Chunk {
+    code: Vec<u8>,
+    constants: Vec<Value>,
+    lines: Vec<u64>,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_byte(&mut self, byte: u8, line: u64) -> usize {
+        self.code.push(byte);
+        self.lines.push(line);
+        self.code.len() - 1
+    }
+
+    pub fn write_op(&mut self, op: OpCode, line: u64) -> usize {
+        self.write_byte(op as u8, line)
+    }
+
+    /// Appends `value` to the constant pool and returns its index, for a
+    /// `Constant` opcode's operand byte.
+    pub fn add_constant(&mut self, value: Value) -> u8 {
+        self.constants.push(value);
+        u8::try_from(self.constants.len() - 1).expect("too many constants in one chunk")
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn constant(&self, index: u8) -> &Value {
+        &self.constants[index as usize]
+    }
+
+    pub fn line(&self, offset: usize) -> u64 {
+        self.lines.get(offset).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
     }
-}
 
-impl<T> DerefMut for Chunk<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    /// Overwrites the `u16` operand at `offset` (the two bytes right after
+    /// a `Jump`/`JumpIfFalse` opcode) with `value`, used to back-patch a
+    /// jump once its target is known.
+    pub fn patch_u16(&mut self, offset: usize, value: u16) {
+        let bytes = value.to_be_bytes();
+        self.code[offset] = bytes[0];
+        self.code[offset + 1] = bytes[1];
     }
 }
@@ -0,0 +1,428 @@
+use std::rc::Rc;
+
+use crate::syntax_tree::{BinaryOperator, Expr, Literal, LogicalOperator, Stmt, UnaryOperator};
+
+use super::chunk::{Chunk, OpCode};
+use super::interner::Interner;
+use super::value::{CompiledFunction, Value};
+
+#[derive(Debug)]
+pub enum CompileError {
+    TooManyLocals,
+    JumpTooFar,
+    /// A construct the bytecode backend doesn't lower yet, e.g. a lambda
+    /// or a function declared inside a block. The tree-walking
+    /// `Interpreter` still handles these; callers that hit this should
+    /// fall back to it rather than treat it as a hard error.
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyLocals => write!(f, "too many local variables in one scope"),
+            Self::JumpTooFar => write!(f, "jump offset too large to encode"),
+            Self::Unsupported(what) => write!(f, "the bytecode backend doesn't support {what} yet"),
+        }
+    }
+}
+
+type CompileResult = Result<(), CompileError>;
+
+struct Local {
+    name: Rc<str>,
+    depth: usize,
+}
+
+/// Lowers a parsed `Stmt`/`Expr` tree into a `Chunk` of bytecode, paying
+/// the tree traversal cost once at compile time instead of once per loop
+/// iteration or call the way `Interpreter` does. Locals are resolved to
+/// stack slots directly by this pass (it doesn't consume the `Resolver`'s
+/// depth annotations); globals go through `interner` so `GetGlobal` and
+/// friends can carry a stable index instead of a name.
+pub struct Compiler<'b> {
+    chunk: Chunk,
+    interner: &'b mut Interner,
+    locals: Vec<Local>,
+    scope_depth: usize,
+}
+
+/// Compiles a whole program into an implicit `<script>` function, callable
+/// by a `Vm` as its entry point. A free function rather than an associated
+/// one: `Compiler<'b>`'s `'b` is tied to whatever `Interner` its caller
+/// already owns, but this needs to create and own that `Interner` itself,
+/// for a lifetime no wider than this call.
+pub fn compile_program(stmts: &[Stmt]) -> Result<(CompiledFunction, Vec<Rc<str>>), CompileError> {
+    let mut interner = Interner::new();
+    let chunk = {
+        let mut compiler = Compiler::new(&mut interner);
+        for stmt in stmts {
+            compiler.statement(stmt)?;
+        }
+        let line = stmts.last().map(script_tail_line).unwrap_or(0);
+        compiler.push_nil(line);
+        compiler.chunk.write_op(OpCode::Return, line);
+        compiler.chunk
+    };
+    let function = CompiledFunction {
+        name: Rc::from("<script>"),
+        arity: 0,
+        chunk,
+    };
+    Ok((function, interner.into_names()))
+}
+
+impl<'b> Compiler<'b> {
+    fn new(interner: &'b mut Interner) -> Self {
+        Self {
+            chunk: Chunk::new(),
+            interner,
+            locals: Vec::new(),
+            scope_depth: 0,
+        }
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> CompileResult {
+        match stmt {
+            Stmt::Expression(expr) => {
+                self.expression(expr)?;
+                self.chunk.write_op(OpCode::Pop, expr.line());
+                Ok(())
+            }
+            Stmt::Print(expr) => {
+                let line = expr.line();
+                self.expression(expr)?;
+                self.chunk.write_op(OpCode::Print, line);
+                Ok(())
+            }
+            Stmt::Var(name, initializer) => {
+                match initializer {
+                    Some(expr) => self.expression(expr)?,
+                    None => self.push_nil(0),
+                }
+                self.define_variable(name)
+            }
+            Stmt::Function(name, params, body) => self.function(name.lexeme(), params, body, name.line()),
+            Stmt::Block(stmts) => {
+                self.begin_scope();
+                for stmt in stmts {
+                    self.statement(stmt)?;
+                }
+                self.end_scope();
+                Ok(())
+            }
+            Stmt::Return(expr) => {
+                let line = expr.as_ref().map(|e| e.line()).unwrap_or(0);
+                match expr {
+                    Some(expr) => self.expression(expr)?,
+                    None => self.push_nil(line),
+                }
+                self.chunk.write_op(OpCode::Return, line);
+                Ok(())
+            }
+            Stmt::If(condition, then_branch, else_branch) => {
+                let line = condition.line();
+                self.expression(condition)?;
+                let then_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                self.chunk.write_op(OpCode::Pop, line);
+                self.statement(then_branch)?;
+                let else_jump = self.emit_jump(OpCode::Jump, line);
+                self.patch_jump(then_jump)?;
+                self.chunk.write_op(OpCode::Pop, line);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch)?;
+                }
+                self.patch_jump(else_jump)
+            }
+            Stmt::While(condition, body) => {
+                let line = condition.line();
+                let loop_start = self.chunk.len();
+                self.expression(condition)?;
+                let exit_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                self.chunk.write_op(OpCode::Pop, line);
+                self.statement(body)?;
+                self.emit_loop(loop_start, line)?;
+                self.patch_jump(exit_jump)?;
+                self.chunk.write_op(OpCode::Pop, line);
+                Ok(())
+            }
+            Stmt::Loop(body) => {
+                let loop_start = self.chunk.len();
+                self.statement(body)?;
+                self.emit_loop(loop_start, 0)
+            }
+            Stmt::DoWhile(condition, body) => {
+                let line = condition.line();
+                let loop_start = self.chunk.len();
+                self.statement(body)?;
+                self.expression(condition)?;
+                let exit_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                self.chunk.write_op(OpCode::Pop, line);
+                self.emit_loop(loop_start, line)?;
+                self.patch_jump(exit_jump)?;
+                self.chunk.write_op(OpCode::Pop, line);
+                Ok(())
+            }
+            Stmt::Use(..) => Err(CompileError::Unsupported("module imports")),
+        }
+    }
+
+    fn function(
+        &mut self,
+        name: Rc<str>,
+        params: &[crate::tokens::Token],
+        body: &[Stmt],
+        line: u64,
+    ) -> CompileResult {
+        if self.scope_depth != 0 {
+            return Err(CompileError::Unsupported("function declarations inside a block"));
+        }
+        let mut nested = Compiler::new(self.interner);
+        nested.scope_depth = 1;
+        for param in params {
+            nested.locals.push(Local { name: param.lexeme(), depth: 1 });
+        }
+        for stmt in body {
+            nested.statement(stmt)?;
+        }
+        nested.push_nil(line);
+        nested.chunk.write_op(OpCode::Return, line);
+
+        let function = Rc::new(CompiledFunction {
+            name: Rc::clone(&name),
+            arity: u8::try_from(params.len()).map_err(|_| CompileError::TooManyLocals)?,
+            chunk: nested.chunk,
+        });
+        let index = self.chunk.add_constant(Value::Function(function));
+        self.chunk.write_op(OpCode::Constant, line);
+        self.chunk.write_byte(index, line);
+        self.define_variable(&name)
+    }
+
+    fn expression(&mut self, expr: &Expr) -> CompileResult {
+        match expr {
+            Expr::Literal(literal) => {
+                let line = expr.line();
+                let index = self.chunk.add_constant(Value::Literal(literal.clone()));
+                self.chunk.write_op(OpCode::Constant, line);
+                self.chunk.write_byte(index, line);
+                Ok(())
+            }
+            Expr::Grouping(inner) => self.expression(inner),
+            Expr::Unary(op, inner, line) => {
+                self.expression(inner)?;
+                self.chunk.write_op(
+                    match op {
+                        UnaryOperator::Minus => OpCode::Negate,
+                        UnaryOperator::Bang => OpCode::Not,
+                    },
+                    *line,
+                );
+                Ok(())
+            }
+            Expr::Binary(left, op, right, line) => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.binary_op(op.clone(), *line);
+                Ok(())
+            }
+            Expr::Variable(token, _) => self.resolve_and_load(token.value(), token.line()),
+            Expr::Assign(token, value, _) => {
+                self.expression(value)?;
+                self.resolve_and_store(token.value(), token.line())
+            }
+            Expr::Call(callee, arguments, line) => {
+                self.expression(callee)?;
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+                let argc = u8::try_from(arguments.len()).map_err(|_| CompileError::TooManyLocals)?;
+                self.chunk.write_op(OpCode::Call, *line);
+                self.chunk.write_byte(argc, *line);
+                Ok(())
+            }
+            Expr::Logical(left, op, right, line) => self.logical(left, op, right, *line),
+            Expr::Lambda(..) => Err(CompileError::Unsupported("lambda expressions")),
+            Expr::Index(..) => Err(CompileError::Unsupported("string indexing")),
+            Expr::Factorial(..) => Err(CompileError::Unsupported("factorial")),
+            Expr::Get(..) => Err(CompileError::Unsupported("property access")),
+            Expr::Set(..) => Err(CompileError::Unsupported("property assignment")),
+        }
+    }
+
+    fn binary_op(&mut self, op: BinaryOperator, line: u64) {
+        match op {
+            BinaryOperator::Plus => {
+                self.chunk.write_op(OpCode::Add, line);
+            }
+            BinaryOperator::Minus => {
+                self.chunk.write_op(OpCode::Sub, line);
+            }
+            BinaryOperator::Star => {
+                self.chunk.write_op(OpCode::Mul, line);
+            }
+            BinaryOperator::Slash => {
+                self.chunk.write_op(OpCode::Div, line);
+            }
+            BinaryOperator::Modulo => {
+                self.chunk.write_op(OpCode::Mod, line);
+            }
+            BinaryOperator::Caret => {
+                self.chunk.write_op(OpCode::Pow, line);
+            }
+            BinaryOperator::BitAnd => {
+                self.chunk.write_op(OpCode::BitAnd, line);
+            }
+            BinaryOperator::BitOr => {
+                self.chunk.write_op(OpCode::BitOr, line);
+            }
+            BinaryOperator::Greater => {
+                self.chunk.write_op(OpCode::Greater, line);
+            }
+            BinaryOperator::Less => {
+                self.chunk.write_op(OpCode::Less, line);
+            }
+            BinaryOperator::EqualEqual => {
+                self.chunk.write_op(OpCode::Equal, line);
+            }
+            // No dedicated opcodes for these; compose them from the ones
+            // above, same as a tree-walker would desugar them.
+            BinaryOperator::GreaterEqual => {
+                self.chunk.write_op(OpCode::Less, line);
+                self.chunk.write_op(OpCode::Not, line);
+            }
+            BinaryOperator::LessEqual => {
+                self.chunk.write_op(OpCode::Greater, line);
+                self.chunk.write_op(OpCode::Not, line);
+            }
+            BinaryOperator::BangEqual => {
+                self.chunk.write_op(OpCode::Equal, line);
+                self.chunk.write_op(OpCode::Not, line);
+            }
+        }
+    }
+
+    fn logical(&mut self, left: &Expr, op: &LogicalOperator, right: &Expr, line: u64) -> CompileResult {
+        self.expression(left)?;
+        match op {
+            LogicalOperator::And => {
+                let end_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                self.chunk.write_op(OpCode::Pop, line);
+                self.expression(right)?;
+                self.patch_jump(end_jump)
+            }
+            LogicalOperator::Or => {
+                let else_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                let end_jump = self.emit_jump(OpCode::Jump, line);
+                self.patch_jump(else_jump)?;
+                self.chunk.write_op(OpCode::Pop, line);
+                self.expression(right)?;
+                self.patch_jump(end_jump)
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scope_depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        self.scope_depth -= 1;
+        while let Some(local) = self.locals.last() {
+            if local.depth <= self.scope_depth {
+                break;
+            }
+            self.locals.pop();
+            self.chunk.write_op(OpCode::Pop, 0);
+        }
+    }
+
+    /// Binds the value currently on top of the stack to `name`: as a new
+    /// local slot inside a block/function, or as a global otherwise.
+    fn define_variable(&mut self, name: &str) -> CompileResult {
+        if self.scope_depth == 0 {
+            let index = self.interner.intern(name);
+            self.chunk.write_op(OpCode::DefineGlobal, 0);
+            self.chunk.write_byte(index, 0);
+        } else {
+            if self.locals.len() >= u8::MAX as usize {
+                return Err(CompileError::TooManyLocals);
+            }
+            self.locals.push(Local { name: Rc::from(name), depth: self.scope_depth });
+        }
+        Ok(())
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<u8> {
+        self.locals
+            .iter()
+            .rposition(|local| &*local.name == name)
+            .map(|index| index as u8)
+    }
+
+    fn resolve_and_load(&mut self, name: &str, line: u64) -> CompileResult {
+        match self.resolve_local(name) {
+            Some(slot) => {
+                self.chunk.write_op(OpCode::GetLocal, line);
+                self.chunk.write_byte(slot, line);
+            }
+            None => {
+                let index = self.interner.intern(name);
+                self.chunk.write_op(OpCode::GetGlobal, line);
+                self.chunk.write_byte(index, line);
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_and_store(&mut self, name: &str, line: u64) -> CompileResult {
+        match self.resolve_local(name) {
+            Some(slot) => {
+                self.chunk.write_op(OpCode::SetLocal, line);
+                self.chunk.write_byte(slot, line);
+            }
+            None => {
+                let index = self.interner.intern(name);
+                self.chunk.write_op(OpCode::SetGlobal, line);
+                self.chunk.write_byte(index, line);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_nil(&mut self, line: u64) {
+        let index = self.chunk.add_constant(Value::Literal(Literal::Nil));
+        self.chunk.write_op(OpCode::Constant, line);
+        self.chunk.write_byte(index, line);
+    }
+
+    fn emit_jump(&mut self, op: OpCode, line: u64) -> usize {
+        self.chunk.write_op(op, line);
+        self.chunk.write_byte(0xff, line);
+        self.chunk.write_byte(0xff, line);
+        self.chunk.len() - 2
+    }
+
+    fn patch_jump(&mut self, offset: usize) -> CompileResult {
+        let jump = self.chunk.len() - offset - 2;
+        let jump = u16::try_from(jump).map_err(|_| CompileError::JumpTooFar)?;
+        self.chunk.patch_u16(offset, jump);
+        Ok(())
+    }
+
+    fn emit_loop(&mut self, loop_start: usize, line: u64) -> CompileResult {
+        self.chunk.write_op(OpCode::Loop, line);
+        let distance = self.chunk.len() - loop_start + 2;
+        let distance = u16::try_from(distance).map_err(|_| CompileError::JumpTooFar)?;
+        self.chunk.write_byte((distance >> 8) as u8, line);
+        self.chunk.write_byte((distance & 0xff) as u8, line);
+        Ok(())
+    }
+}
+
+fn script_tail_line(stmt: &Stmt) -> u64 {
+    match stmt {
+        Stmt::Expression(expr) | Stmt::Print(expr) => expr.line(),
+        _ => 0,
+    }
+}
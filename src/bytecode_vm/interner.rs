@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Maps identifier strings to small stable indices, so a `GetGlobal`-style
+/// opcode can carry a single operand byte instead of re-hashing (or
+/// re-storing) the name at every reference.
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: Vec<Rc<str>>,
+    indices: HashMap<Rc<str>, u8>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> u8 {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        let index = u8::try_from(self.names.len()).expect("too many distinct identifiers in one program");
+        let name: Rc<str> = Rc::from(name);
+        self.names.push(Rc::clone(&name));
+        self.indices.insert(name, index);
+        index
+    }
+
+    /// Hands over the index -> name table once compilation is done, so a
+    /// `Vm` can report *which* global was undefined instead of just that
+    /// one was.
+    pub fn into_names(self) -> Vec<Rc<str>> {
+        self.names
+    }
+}
@@ -0,0 +1,136 @@
+//! An alternative execution backend: instead of `Interpreter` walking the
+//! `Stmt`/`Expr` tree directly (re-visiting it on every loop iteration and
+//! call), `Compiler` lowers it once into a `Chunk` of bytecode that `Vm`
+//! then runs against an explicit value stack. Exposed behind the same
+//! public surface as the tree-walker so callers can pick either backend.
+mod chunk;
+mod compiler;
+mod interner;
+mod value;
+mod vm;
+
+use compiler::{compile_program, CompileError};
+use vm::{Vm, VmError};
+
+use crate::syntax_tree::Stmt;
+
+/// Compiles `stmts` and runs them to completion on a fresh `Vm`, mirroring
+/// `Interpreter::evaluate`'s all-in-one convenience for the tree-walking
+/// backend.
+pub fn run(stmts: &[Stmt]) -> Result<(), RunError> {
+    let (script, names) = compile_program(stmts).map_err(RunError::Compile)?;
+    Vm::new(script, names).run().map_err(RunError::Runtime)
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Compile(CompileError),
+    Runtime(VmError),
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compile(err) => write!(f, "{err}"),
+            Self::Runtime(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::vm::VmErrorKind;
+    use super::*;
+    use crate::syntax_tree::{BinaryOperator, Expr, Literal, UnaryOperator};
+    use crate::tokens::{Token, TokenType};
+
+    fn num(n: f64) -> Expr {
+        Expr::literal(Literal::Number(n))
+    }
+
+    fn var(name: &str, line: u64) -> Expr {
+        Expr::variable(Token::new(TokenType::Identifier, name, line))
+    }
+
+    fn assign(name: &str, value: Expr, line: u64) -> Stmt {
+        Stmt::Expression(Expr::assign(Token::new(TokenType::Identifier, name, line), value))
+    }
+
+    /// Compiles down to "if not cond, divide by zero", so a wrong result
+    /// surfaces as a `VmErrorKind::DivisionByZero` runtime error instead of
+    /// the `run()` caller needing a way to peek at the `Vm`'s private
+    /// stack/globals - `run`'s only public signal is this `Result`.
+    fn assert_stmt(cond: Expr, line: u64) -> Stmt {
+        Stmt::If(
+            Expr::unary(UnaryOperator::Bang, cond, line),
+            Box::new(Stmt::Expression(Expr::binary(num(1.0), BinaryOperator::Slash, num(0.0), line))),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_arithmetic_and_globals() {
+        let stmts = vec![
+            Stmt::Var(
+                Rc::from("x"),
+                Some(Expr::binary(num(1.0), BinaryOperator::Plus, Expr::binary(num(2.0), BinaryOperator::Star, num(3.0), 1), 1)),
+            ),
+            assert_stmt(Expr::binary(var("x", 1), BinaryOperator::EqualEqual, num(7.0), 1), 1),
+        ];
+        assert!(run(&stmts).is_ok());
+    }
+
+    #[test]
+    fn test_assert_pattern_surfaces_division_by_zero_on_wrong_result() {
+        let stmts = vec![
+            Stmt::Var(Rc::from("x"), Some(Expr::binary(num(1.0), BinaryOperator::Plus, num(1.0), 1))),
+            assert_stmt(Expr::binary(var("x", 1), BinaryOperator::EqualEqual, num(3.0), 1), 1),
+        ];
+        match run(&stmts) {
+            Err(RunError::Runtime(err)) => assert!(matches!(err.kind, VmErrorKind::DivisionByZero)),
+            other => panic!("expected a division-by-zero runtime error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_while_loop_accumulator() {
+        let stmts = vec![
+            Stmt::Var(Rc::from("sum"), Some(num(0.0))),
+            Stmt::Var(Rc::from("i"), Some(num(0.0))),
+            Stmt::While(
+                Expr::binary(var("i", 2), BinaryOperator::Less, num(5.0), 2),
+                Box::new(Stmt::Block(vec![
+                    assign("sum", Expr::binary(var("sum", 2), BinaryOperator::Plus, var("i", 2), 2), 2),
+                    assign("i", Expr::binary(var("i", 2), BinaryOperator::Plus, num(1.0), 2), 2),
+                ])),
+            ),
+            assert_stmt(Expr::binary(var("sum", 3), BinaryOperator::EqualEqual, num(10.0), 3), 3),
+        ];
+        assert!(run(&stmts).is_ok());
+    }
+
+    #[test]
+    fn test_function_call_round_trip() {
+        let stmts = vec![
+            Stmt::Function(
+                Token::new(TokenType::Identifier, "add", 1),
+                vec![Token::new(TokenType::Identifier, "a", 1), Token::new(TokenType::Identifier, "b", 1)],
+                vec![Stmt::Return(Some(Expr::binary(var("a", 1), BinaryOperator::Plus, var("b", 1), 1)))],
+            ),
+            Stmt::Var(Rc::from("r"), Some(Expr::call(var("add", 2), vec![num(2.0), num(3.0)], 2))),
+            assert_stmt(Expr::binary(var("r", 2), BinaryOperator::EqualEqual, num(5.0), 2), 2),
+        ];
+        assert!(run(&stmts).is_ok());
+    }
+
+    #[test]
+    fn test_unsupported_construct_falls_back_to_compile_error() {
+        let stmts = vec![Stmt::Expression(Expr::lambda(vec![], vec![Stmt::Return(None)], 1))];
+        match run(&stmts) {
+            Err(RunError::Compile(CompileError::Unsupported(_))) => {}
+            other => panic!("expected a CompileError::Unsupported, got {other:?}"),
+        }
+    }
+}
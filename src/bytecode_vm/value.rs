@@ -0,0 +1,42 @@
+use std::rc::Rc;
+
+use crate::syntax_tree::Literal;
+
+use super::chunk::Chunk;
+
+/// A function lowered to bytecode: its own independent `Chunk`, called
+/// through a fresh `CallFrame` that gives it a window onto the `Vm`'s
+/// shared stack starting at its arguments. Unlike the tree-walking
+/// `Interpreter`'s `Function`, this doesn't capture an enclosing
+/// environment — the bytecode backend only supports plain global
+/// functions for now, not closures.
+#[derive(Debug)]
+pub struct CompiledFunction {
+    pub name: Rc<str>,
+    pub arity: u8,
+    pub chunk: Chunk,
+}
+
+/// What the `Vm`'s stack and constant pool hold. Distinct from the tree
+/// walker's own `environment::Value` because the two backends evolve
+/// independently, but the same split: a plain `Literal`, or a callable.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Literal(Literal),
+    Function(Rc<CompiledFunction>),
+}
+
+impl From<Literal> for Value {
+    fn from(value: Literal) -> Self {
+        Self::Literal(value)
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(literal) => write!(f, "{literal}"),
+            Self::Function(function) => write!(f, "<fn {}>", function.name),
+        }
+    }
+}
@@ -1,28 +1,393 @@
-use super::{chunk::Chunk, values::Value};
+use std::rc::Rc;
 
-const STACK_MAX: usize = 256;
+use crate::syntax_tree::Literal;
 
+use super::chunk::OpCode;
+use super::value::{CompiledFunction, Value};
+
+/// Mirrors `interpreter::MAX_CALL_DEPTH`: caps how many nested `CallFrame`s
+/// the `Vm` will stack up, so runaway recursion ends in a clean
+/// `VmErrorKind::StackOverflow` error instead of exhausting memory.
+const MAX_CALL_DEPTH: usize = 1024;
+
+#[derive(Debug)]
+pub enum VmErrorKind {
+    TypeError,
+    UndefinedGlobal(String),
+    DivisionByZero,
+    NotCallable,
+    ArityMismatch { expected: usize, got: usize },
+    StackOverflow,
+}
+
+#[derive(Debug)]
+pub struct VmError {
+    pub line: u64,
+    pub kind: VmErrorKind,
+}
+
+impl VmError {
+    fn new(line: u64, kind: VmErrorKind) -> Self {
+        Self { line, kind }
+    }
+}
+
+impl std::fmt::Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match &self.kind {
+            VmErrorKind::TypeError => "operands must be of a compatible type".to_string(),
+            VmErrorKind::UndefinedGlobal(name) => format!("undefined variable '{}'", name),
+            VmErrorKind::DivisionByZero => "division by zero".to_string(),
+            VmErrorKind::NotCallable => "value is not callable".to_string(),
+            VmErrorKind::ArityMismatch { expected, got } => {
+                format!("expected {} argument(s) but got {}", expected, got)
+            }
+            VmErrorKind::StackOverflow => "stack overflow".to_string(),
+        };
+        write!(f, "[line {}] Error: {}", self.line, message)
+    }
+}
+
+/// One call's window onto the `Vm`'s shared value stack: which function
+/// it's running, where in that function's chunk it is, and where its
+/// locals (starting with its arguments) begin on the stack.
+struct CallFrame {
+    function: Rc<CompiledFunction>,
+    ip: usize,
+    base: usize,
+}
+
+/// Executes a `CompiledFunction` directly against an explicit value stack
+/// and instruction pointer, as an alternative to `Interpreter` walking the
+/// `Stmt`/`Expr` tree on every iteration.
 pub struct Vm {
-    //TODO: we'll need either pointers, usize to point to the location or refs
-    chunk: Chunk<Value>,
-    ip: u8,                            // NOTE: this is a pointer to the values in the chunk
-    stack: Vec<Value> //TODO: use maybeuninit
+    frames: Vec<CallFrame>,
+    stack: Vec<Value>,
+    globals: Vec<Option<Value>>,
+    global_names: Vec<Rc<str>>,
 }
 
 impl Vm {
-    pub fn new(chunk: Chunk<Value>) -> Self {
+    pub fn new(script: CompiledFunction, global_names: Vec<Rc<str>>) -> Self {
         Self {
-            chunk,
-            ip: 0,
-            stack: Vec::with_capacity(STACK_MAX),
+            frames: vec![CallFrame { function: Rc::new(script), ip: 0, base: 0 }],
+            stack: Vec::new(),
+            globals: Vec::new(),
+            global_names,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), VmError> {
+        loop {
+            let frame = self.frames.len() - 1;
+            let ip = self.frames[frame].ip;
+            let op = OpCode::from_u8(self.frames[frame].function.chunk.code()[ip]);
+            let line = self.frames[frame].function.chunk.line(ip);
+            self.frames[frame].ip += 1;
+
+            match op {
+                OpCode::Constant => {
+                    let index = self.read_byte(frame);
+                    let value = self.frames[frame].function.chunk.constant(index).clone();
+                    self.stack.push(value);
+                }
+                OpCode::Add => self.binary_numeric_or_concat(line)?,
+                OpCode::Sub => self.binary_numeric(line, |l, r| l - r)?,
+                OpCode::Mul => self.binary_numeric(line, |l, r| l * r)?,
+                OpCode::Div => self.binary_checked(line, |l, r| {
+                    if r == 0.0 {
+                        Err(VmErrorKind::DivisionByZero)
+                    } else {
+                        Ok(Literal::Number(l / r))
+                    }
+                })?,
+                OpCode::Mod => self.binary_checked(line, |l, r| {
+                    if r == 0.0 {
+                        Err(VmErrorKind::DivisionByZero)
+                    } else {
+                        Ok(Literal::Number(l % r))
+                    }
+                })?,
+                OpCode::Pow => self.binary_numeric(line, |l, r| l.powf(r))?,
+                OpCode::BitAnd => self.binary_numeric(line, |l, r| ((l as i64) & (r as i64)) as f64)?,
+                OpCode::BitOr => self.binary_numeric(line, |l, r| ((l as i64) | (r as i64)) as f64)?,
+                OpCode::Negate => {
+                    let value = self.pop_number(line)?;
+                    self.stack.push(Value::Literal(Literal::Number(-value)));
+                }
+                OpCode::Not => {
+                    let value = self.stack.pop().expect("stack underflow");
+                    let truthy = matches!(value, Value::Literal(literal) if literal.is_truthy());
+                    self.stack.push(Value::Literal(bool_literal(!truthy)));
+                }
+                OpCode::Equal => {
+                    let right = self.stack.pop().expect("stack underflow");
+                    let left = self.stack.pop().expect("stack underflow");
+                    let equal = matches!((&left, &right), (Value::Literal(l), Value::Literal(r)) if l == r);
+                    self.stack.push(Value::Literal(bool_literal(equal)));
+                }
+                OpCode::Less => self.compare(line, |l, r| l < r)?,
+                OpCode::Greater => self.compare(line, |l, r| l > r)?,
+                OpCode::Print => {
+                    let value = self.stack.pop().expect("stack underflow");
+                    println!("{value}");
+                }
+                OpCode::Pop => {
+                    self.stack.pop();
+                }
+                OpCode::DefineGlobal => {
+                    let index = self.read_byte(frame) as usize;
+                    let value = self.stack.pop().expect("stack underflow");
+                    if index >= self.globals.len() {
+                        self.globals.resize(index + 1, None);
+                    }
+                    self.globals[index] = Some(value);
+                }
+                OpCode::GetGlobal => {
+                    let index = self.read_byte(frame) as usize;
+                    match self.globals.get(index).and_then(Clone::clone) {
+                        Some(value) => self.stack.push(value),
+                        None => return Err(self.undefined_global(line, index)),
+                    }
+                }
+                OpCode::SetGlobal => {
+                    let index = self.read_byte(frame) as usize;
+                    if index >= self.globals.len() || self.globals[index].is_none() {
+                        return Err(self.undefined_global(line, index));
+                    }
+                    self.globals[index] = Some(self.stack.last().expect("stack underflow").clone());
+                }
+                OpCode::GetLocal => {
+                    let slot = self.read_byte(frame) as usize;
+                    let base = self.frames[frame].base;
+                    self.stack.push(self.stack[base + slot].clone());
+                }
+                OpCode::SetLocal => {
+                    let slot = self.read_byte(frame) as usize;
+                    let base = self.frames[frame].base;
+                    self.stack[base + slot] = self.stack.last().expect("stack underflow").clone();
+                }
+                OpCode::Jump => {
+                    let offset = self.read_u16(frame);
+                    self.frames[frame].ip += offset as usize;
+                }
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_u16(frame);
+                    let truthy =
+                        matches!(self.stack.last(), Some(Value::Literal(literal)) if literal.is_truthy());
+                    if !truthy {
+                        self.frames[frame].ip += offset as usize;
+                    }
+                }
+                OpCode::Loop => {
+                    let distance = self.read_u16(frame);
+                    self.frames[frame].ip -= distance as usize;
+                }
+                OpCode::Call => {
+                    let argc = self.read_byte(frame) as usize;
+                    self.call(argc, line)?;
+                }
+                OpCode::Return => {
+                    let result = self.stack.pop().unwrap_or(Value::Literal(Literal::Nil));
+                    if self.frames.len() == 1 {
+                        return Ok(());
+                    }
+                    let callee_slot = self.frames[frame].base - 1;
+                    self.frames.pop();
+                    self.stack.truncate(callee_slot);
+                    self.stack.push(result);
+                }
+            }
+        }
+    }
+
+    fn call(&mut self, argc: usize, line: u64) -> Result<(), VmError> {
+        let callee_slot = self.stack.len() - argc - 1;
+        match self.stack[callee_slot].clone() {
+            Value::Function(function) => {
+                if function.arity as usize != argc {
+                    return Err(VmError::new(
+                        line,
+                        VmErrorKind::ArityMismatch { expected: function.arity as usize, got: argc },
+                    ));
+                }
+                if self.frames.len() >= MAX_CALL_DEPTH {
+                    return Err(VmError::new(line, VmErrorKind::StackOverflow));
+                }
+                self.frames.push(CallFrame { function, ip: 0, base: callee_slot + 1 });
+                Ok(())
+            }
+            Value::Literal(_) => Err(VmError::new(line, VmErrorKind::NotCallable)),
+        }
+    }
+
+    fn undefined_global(&self, line: u64, index: usize) -> VmError {
+        let name = self.global_names.get(index).map(|n| n.to_string()).unwrap_or_else(|| "<unknown>".to_string());
+        VmError::new(line, VmErrorKind::UndefinedGlobal(name))
+    }
+
+    fn read_byte(&mut self, frame: usize) -> u8 {
+        let ip = self.frames[frame].ip;
+        self.frames[frame].ip += 1;
+        self.frames[frame].function.chunk.code()[ip]
+    }
+
+    fn read_u16(&mut self, frame: usize) -> u16 {
+        let hi = self.read_byte(frame);
+        let lo = self.read_byte(frame);
+        u16::from_be_bytes([hi, lo])
+    }
+
+    fn pop_number(&mut self, line: u64) -> Result<f64, VmError> {
+        match self.stack.pop() {
+            Some(Value::Literal(Literal::Number(n))) => Ok(n),
+            _ => Err(VmError::new(line, VmErrorKind::TypeError)),
         }
     }
-    fn pop(&mut self) -> Option<Value> {
-        self.stack.pop()
+
+    fn binary_numeric(&mut self, line: u64, op: impl Fn(f64, f64) -> f64) -> Result<(), VmError> {
+        let right = self.pop_number(line)?;
+        let left = self.pop_number(line)?;
+        self.stack.push(Value::Literal(Literal::Number(op(left, right))));
+        Ok(())
+    }
+
+    fn binary_checked(
+        &mut self,
+        line: u64,
+        op: impl Fn(f64, f64) -> Result<Literal, VmErrorKind>,
+    ) -> Result<(), VmError> {
+        let right = self.pop_number(line)?;
+        let left = self.pop_number(line)?;
+        let result = op(left, right).map_err(|kind| VmError::new(line, kind))?;
+        self.stack.push(Value::Literal(result));
+        Ok(())
+    }
+
+    fn compare(&mut self, line: u64, op: impl Fn(f64, f64) -> bool) -> Result<(), VmError> {
+        let right = self.pop_number(line)?;
+        let left = self.pop_number(line)?;
+        self.stack.push(Value::Literal(bool_literal(op(left, right))));
+        Ok(())
+    }
+
+    /// `+` additionally accepts a pair of strings, concatenating them the
+    /// same way `Interpreter::evaluate_binary` does.
+    fn binary_numeric_or_concat(&mut self, line: u64) -> Result<(), VmError> {
+        let right = self.stack.pop().expect("stack underflow");
+        let left = self.stack.pop().expect("stack underflow");
+        let result = match (left, right) {
+            (Value::Literal(Literal::Number(l)), Value::Literal(Literal::Number(r))) => Literal::Number(l + r),
+            (Value::Literal(Literal::String(l)), Value::Literal(Literal::String(r))) => {
+                Literal::String(Rc::from(format!("{l}{r}")))
+            }
+            _ => return Err(VmError::new(line, VmErrorKind::TypeError)),
+        };
+        self.stack.push(Value::Literal(result));
+        Ok(())
+    }
+}
+
+fn bool_literal(value: bool) -> Literal {
+    if value {
+        Literal::True
+    } else {
+        Literal::False
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::chunk::Chunk;
+    use super::*;
+
+    fn script(chunk: Chunk) -> CompiledFunction {
+        CompiledFunction { name: Rc::from("<script>"), arity: 0, chunk }
+    }
+
+    #[test]
+    fn test_add_define_global_then_return_leaves_global_bound() {
+        let mut chunk = Chunk::new();
+        let a = chunk.add_constant(Value::Literal(Literal::Number(1.0)));
+        let b = chunk.add_constant(Value::Literal(Literal::Number(2.0)));
+        let nil = chunk.add_constant(Value::Literal(Literal::Nil));
+        chunk.write_op(OpCode::Constant, 1);
+        chunk.write_byte(a, 1);
+        chunk.write_op(OpCode::Constant, 1);
+        chunk.write_byte(b, 1);
+        chunk.write_op(OpCode::Add, 1);
+        chunk.write_op(OpCode::DefineGlobal, 1);
+        chunk.write_byte(0, 1);
+        chunk.write_op(OpCode::Constant, 1);
+        chunk.write_byte(nil, 1);
+        chunk.write_op(OpCode::Return, 1);
+
+        let mut vm = Vm::new(script(chunk), vec![Rc::from("x")]);
+        assert!(vm.run().is_ok());
+        assert!(matches!(vm.globals[0], Some(Value::Literal(Literal::Number(n))) if n == 3.0));
+    }
+
+    #[test]
+    fn test_sub_on_string_and_number_is_a_type_error_at_its_line() {
+        let mut chunk = Chunk::new();
+        let s = chunk.add_constant(Value::Literal(Literal::String(Rc::from("foo"))));
+        let n = chunk.add_constant(Value::Literal(Literal::Number(1.0)));
+        chunk.write_op(OpCode::Constant, 7);
+        chunk.write_byte(s, 7);
+        chunk.write_op(OpCode::Constant, 7);
+        chunk.write_byte(n, 7);
+        chunk.write_op(OpCode::Sub, 7);
+
+        let mut vm = Vm::new(script(chunk), Vec::new());
+        let err = vm.run().unwrap_err();
+        assert!(matches!(err.kind, VmErrorKind::TypeError));
+        assert_eq!(err.line, 7);
+    }
+
+    #[test]
+    fn test_div_by_zero_is_division_by_zero_at_its_line() {
+        let mut chunk = Chunk::new();
+        let a = chunk.add_constant(Value::Literal(Literal::Number(1.0)));
+        let b = chunk.add_constant(Value::Literal(Literal::Number(0.0)));
+        chunk.write_op(OpCode::Constant, 3);
+        chunk.write_byte(a, 3);
+        chunk.write_op(OpCode::Constant, 3);
+        chunk.write_byte(b, 3);
+        chunk.write_op(OpCode::Div, 3);
+
+        let mut vm = Vm::new(script(chunk), Vec::new());
+        let err = vm.run().unwrap_err();
+        assert!(matches!(err.kind, VmErrorKind::DivisionByZero));
+        assert_eq!(err.line, 3);
     }
-    fn push(&mut self, value: Value) {
-        self.stack.push(value)
+
+    /// A global bound to a function that calls itself through that same
+    /// global, forever. `MAX_CALL_DEPTH` should stop this as a clean
+    /// `StackOverflow` runtime error well before it exhausts the real
+    /// native call stack.
+    #[test]
+    fn test_unbounded_recursion_hits_stack_overflow_not_a_crash() {
+        let mut recurse_chunk = Chunk::new();
+        recurse_chunk.write_op(OpCode::GetGlobal, 1);
+        recurse_chunk.write_byte(0, 1);
+        recurse_chunk.write_op(OpCode::Call, 1);
+        recurse_chunk.write_byte(0, 1);
+        recurse_chunk.write_op(OpCode::Return, 1);
+        let recurse = Rc::new(CompiledFunction { name: Rc::from("f"), arity: 0, chunk: recurse_chunk });
+
+        let mut chunk = Chunk::new();
+        let f = chunk.add_constant(Value::Function(recurse));
+        chunk.write_op(OpCode::Constant, 1);
+        chunk.write_byte(f, 1);
+        chunk.write_op(OpCode::DefineGlobal, 1);
+        chunk.write_byte(0, 1);
+        chunk.write_op(OpCode::GetGlobal, 1);
+        chunk.write_byte(0, 1);
+        chunk.write_op(OpCode::Call, 1);
+        chunk.write_byte(0, 1);
+
+        let mut vm = Vm::new(script(chunk), vec![Rc::from("f")]);
+        let err = vm.run().unwrap_err();
+        assert!(matches!(err.kind, VmErrorKind::StackOverflow));
     }
-    fn run(&mut self){}
-    pub fn interpret(&mut self, chunk: Chunk<Value>){}
 }
@@ -1,20 +1,196 @@
-use std::{collections::HashMap, ops::{Deref, DerefMut}};
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
-use crate::syntax_tree::Literal;
+use crate::{
+    syntax_tree::{Literal, Stmt},
+    tokens::Token,
+};
 
-#[derive(Default)]
-pub struct Environment<'a>(HashMap<&'a str, Literal<'a>>);
+/// A function declared in the language itself, closing over the
+/// environment it was defined in plus its own parameter tokens and
+/// body. Capturing `closure` (rather than just running against whatever
+/// environment happens to be current at call time) is what makes
+/// counter-style closures and recursion through the global scope work.
+///
+/// Declaring a named function stores its `Value::Function` in the very
+/// scope `closure` points back to, which is a self-referential `Rc` -
+/// that scope's own refcount never drops to zero on its own once that
+/// happens. Accepted tradeoff for a tree-walking interpreter rather than
+/// a bug: it costs nothing for the global scope (kept alive for the
+/// program's whole lifetime regardless) and for a short-lived script
+/// process the OS reclaims everything at exit anyway; it would only
+/// matter for a long-running embedder that declares and discards many
+/// functions in short-lived local scopes.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+    pub closure: Environment,
+}
+
+/// A function implemented in Rust and exposed to scripts under a name.
+/// `name` is always a Rust string literal, never user source text, so it
+/// can stay a plain `&'static str` even though everything derived from
+/// parsed source owns its lexeme.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    /// Required argument count, checked exactly against the call site -
+    /// except for the sentinel [`VARIADIC`], which skips the check
+    /// entirely so natives like `min`/`max`/`sum` can take any number of
+    /// arguments.
+    pub arity: usize,
+    pub func: fn(&[Value]) -> Value,
+}
+
+/// Sentinel `arity` for a native that accepts any number of arguments.
+pub const VARIADIC: usize = usize::MAX;
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction").field("name", &self.name).finish()
+    }
+}
+
+/// The runtime value an expression evaluates to. Distinct from `Literal`,
+/// the AST-level constant, because the interpreter also has to carry
+/// first-class functions around once callables exist.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Literal(Literal),
+    Function(Function),
+    NativeFunction(NativeFunction),
+}
 
-impl<'a> Deref for Environment<'a> {
-    type Target = HashMap<&'a str, Literal<'a>>;
+impl From<Literal> for Value {
+    fn from(value: Literal) -> Self {
+        Self::Literal(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotALiteral;
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl Display for NotALiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "value is not a literal")
     }
 }
 
-impl<'a> DerefMut for Environment<'a> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl Value {
+    /// Unwraps a plain literal, erroring when the value is actually a
+    /// callable. Most expression contexts (arithmetic, comparisons) only
+    /// make sense on literals.
+    pub fn expect_literal(self) -> Result<Literal, NotALiteral> {
+        match self {
+            Self::Literal(lit) => Ok(lit),
+            Self::Function(_) | Self::NativeFunction(_) => Err(NotALiteral),
+        }
+    }
+}
+
+/// The bindings owned by one lexical scope, plus an optional link to the
+/// scope it was opened inside of.
+#[derive(Debug, Default)]
+struct Scope {
+    values: HashMap<Rc<str>, Value>,
+    enclosing: Option<Environment>,
+}
+
+/// A lexical scope, shared by reference: cloning an `Environment` clones
+/// the handle, not the bindings, so a closure captured into a `Function`
+/// and the scope it was captured from keep observing each other's
+/// mutations. Name lookup and assignment walk the `enclosing` chain
+/// outward so a block can shadow an outer variable without clobbering it.
+#[derive(Debug, Clone, Default)]
+pub struct Environment(Rc<RefCell<Scope>>);
+
+impl Environment {
+    /// Opens a new child scope enclosed by `self`, ready to be installed
+    /// in place of the current environment for the duration of a block.
+    pub fn child(&self) -> Self {
+        Self(Rc::new(RefCell::new(Scope {
+            values: HashMap::new(),
+            enclosing: Some(self.clone()),
+        })))
+    }
+
+    /// The enclosing scope, if any.
+    fn enclosing(&self) -> Option<Self> {
+        self.0.borrow().enclosing.clone()
+    }
+
+    /// The enclosing scope, restoring it in place of the innermost one.
+    /// Panics if called on the global scope, which has nothing to pop into.
+    pub fn pop(&self) -> Self {
+        self.enclosing().expect("no enclosing scope to restore")
+    }
+
+    pub fn insert(&self, name: Rc<str>, value: Value) {
+        self.0.borrow_mut().values.insert(name, value);
+    }
+
+    /// Every binding this scope itself holds, ignoring anything enclosing
+    /// scopes define. Used to import a whole module's exports at once
+    /// rather than one name at a time.
+    pub fn entries(&self) -> Vec<(Rc<str>, Value)> {
+        self.0.borrow().values.iter().map(|(name, value)| (Rc::clone(name), value.clone())).collect()
+    }
+
+    /// Walks the `enclosing` chain outward looking for `name`, stopping at
+    /// the first scope that binds it. Iterative rather than recursive so a
+    /// deeply nested call stack doesn't also mean a deep native stack here.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let mut current = self.clone();
+        loop {
+            let scope = current.0.borrow();
+            if let Some(value) = scope.values.get(name) {
+                return Some(value.clone());
+            }
+            let next = scope.enclosing.clone()?;
+            drop(scope);
+            current = next;
+        }
+    }
+
+    /// Updates an existing binding, searching outward through enclosing
+    /// scopes, without creating a new one. Returns the assigned value so
+    /// callers (assignment expressions) can use it as the expression's
+    /// result, or `None` if the name isn't bound anywhere.
+    pub fn assign(&self, name: &str, value: Value) -> Option<Value> {
+        let mut current = self.clone();
+        loop {
+            let mut scope = current.0.borrow_mut();
+            if let Some(slot) = scope.values.get_mut(name) {
+                *slot = value.clone();
+                return Some(value);
+            }
+            let next = scope.enclosing.clone()?;
+            drop(scope);
+            current = next;
+        }
+    }
+
+    fn ancestor(&self, depth: usize) -> Option<Self> {
+        let mut env = self.clone();
+        for _ in 0..depth {
+            env = env.enclosing()?;
+        }
+        Some(env)
+    }
+
+    /// Resolver-guided lookup: jumps exactly `depth` enclosing scopes up
+    /// instead of searching, per the distance a `Resolver` recorded for
+    /// this variable reference.
+    pub fn get_at(&self, depth: usize, name: &str) -> Option<Value> {
+        self.ancestor(depth)?.0.borrow().values.get(name).cloned()
+    }
+
+    pub fn assign_at(&self, depth: usize, name: &str, value: Value) -> Option<Value> {
+        let env = self.ancestor(depth)?;
+        let mut scope = env.0.borrow_mut();
+        let slot = scope.values.get_mut(name)?;
+        *slot = value.clone();
+        Some(value)
     }
 }
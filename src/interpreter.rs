@@ -1,28 +1,370 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::{
-    environment::Environment,
-    syntax_tree::{BinaryOperator, Expr, Literal, Stmt, UnaryOperator},
+    environment::{Environment, Function, NativeFunction, Value},
+    syntax_tree::{BinaryOperator, Expr, Literal, LogicalOperator, Stmt, UnaryOperator},
+    tokens::{Token, TokenType},
 };
 
+/// How many nested `Function` calls we'll follow before giving up. Closures
+/// make unbounded recursion easy to write by accident (a counter factory
+/// that calls itself, mutual recursion with no base case); without a limit
+/// that blows the real Rust call stack instead of surfacing as a script error.
+const MAX_CALL_DEPTH: usize = 1024;
+
 #[derive(Debug)]
-pub enum InterpreterError {
-    WrongValue,
+pub enum ErrorKind {
+    TypeError,
+    NotCallable,
+    UndefinedVariable(String),
+    DivisionByZero,
+    ArityMismatch { expected: usize, got: usize },
+    StackOverflow,
+    /// `use` named a module, or a name inside one, that isn't registered.
+    UnknownModule(String),
+    /// A string index (`s[i]`) fell outside `0..s.chars().count()`.
+    IndexOutOfBounds,
+    /// A computation (currently only `!`) produced a result past the
+    /// largest integer an `f64` can represent exactly.
+    Overflow,
+}
+
+#[derive(Debug)]
+pub struct InterpreterError {
+    pub line: u64,
+    pub kind: ErrorKind,
+}
+
+impl InterpreterError {
+    fn new(line: u64, kind: ErrorKind) -> Self {
+        Self { line, kind }
+    }
+}
+
+impl std::fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match &self.kind {
+            ErrorKind::TypeError => "operands must be of a compatible type".to_string(),
+            ErrorKind::NotCallable => "value is not callable".to_string(),
+            ErrorKind::UndefinedVariable(name) => format!("undefined variable '{}'", name),
+            ErrorKind::DivisionByZero => "division by zero".to_string(),
+            ErrorKind::ArityMismatch { expected, got } => {
+                format!("expected {} argument(s) but got {}", expected, got)
+            }
+            ErrorKind::StackOverflow => "stack overflow".to_string(),
+            ErrorKind::UnknownModule(path) => format!("no such module or export '{}'", path),
+            ErrorKind::IndexOutOfBounds => "index out of bounds".to_string(),
+            ErrorKind::Overflow => "result is too large to represent exactly".to_string(),
+        };
+        write!(f, "[line {}] Error: {}", self.line, message)
+    }
+}
+
+pub type InterpreterResult = Result<Value, InterpreterError>;
+
+/// What a statement can unwind with: a real runtime error, or a `return`
+/// unwinding out of the innermost function body. `Return` isn't an error
+/// at all, just reused as the `?`-propagated signal blocks and loops pass
+/// upward until `evaluate_call` catches it at the function boundary.
+#[derive(Debug)]
+pub enum ControlFlow {
+    Error(InterpreterError),
+    /// The `Literal`-only version of this used to force every returned
+    /// value through `expect_literal`, which made it impossible to
+    /// `return` a closure out of a function — exactly the counter/
+    /// factory pattern closures exist for. Carrying the full `Value`
+    /// keeps a returned function callable by whoever catches it.
+    Return(Value),
+}
+
+impl From<InterpreterError> for ControlFlow {
+    fn from(err: InterpreterError) -> Self {
+        Self::Error(err)
+    }
+}
+
+type StmtResult = Result<(), ControlFlow>;
+
+fn is_numeric(lit: &Literal) -> bool {
+    matches!(lit, Literal::Number(_) | Literal::Complex(_, _))
+}
+
+fn as_complex(lit: Literal) -> (f64, f64) {
+    match lit {
+        Literal::Number(n) => (n, 0.0),
+        Literal::Complex(re, im) => (re, im),
+        _ => unreachable!("as_complex called on a non-numeric literal"),
+    }
+}
+
+fn bool_literal(value: bool) -> Literal {
+    if value {
+        Literal::True
+    } else {
+        Literal::False
+    }
+}
+
+/// Halts the process immediately with the given exit code (truncated to
+/// `i32`, defaulting to 0 for anything that isn't a number), so a script
+/// has a way to signal success/failure to whatever invoked it without
+/// just letting control fall off the end of the file.
+fn exit(args: &[Value]) -> Value {
+    let code = match args.first() {
+        Some(Value::Literal(Literal::Number(n))) => *n as i32,
+        _ => 0,
+    };
+    std::process::exit(code);
+}
+
+fn clock(_args: &[Value]) -> Value {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the Unix epoch")
+        .as_secs_f64();
+    Value::Literal(Literal::Number(secs))
+}
+
+/// Reads one line from stdin, so scripts can do interactive I/O (e.g.
+/// `while (true) { print(input()); }`). Any read failure just yields an
+/// empty line rather than aborting the interpreter.
+fn input(_args: &[Value]) -> Value {
+    io::stdout().flush().ok();
+    let mut line = String::new();
+    let _ = io::stdin().lock().read_line(&mut line);
+    let trimmed = line.trim_end_matches(['\n', '\r']);
+    Value::Literal(Literal::String(Rc::from(trimmed)))
+}
+
+/// The length of a string; any other argument type has no length, so it
+/// yields `nil` rather than panicking on a builtin misuse.
+fn len(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(Literal::String(s))) => Value::Literal(Literal::Number(s.len() as f64)),
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Stringifies any literal using its `Display` impl.
+fn str(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(lit)) => {
+            let owned = format!("{lit}");
+            Value::Literal(Literal::String(Rc::from(owned)))
+        }
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Parses a string into a number; numbers pass through unchanged.
+/// Unparseable input yields `nil` instead of panicking, since this
+/// conversion has no error channel of its own.
+fn num(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(Literal::Number(n))) => Value::Literal(Literal::Number(*n)),
+        Some(Value::Literal(Literal::String(s))) => match s.trim().parse::<f64>() {
+            Ok(n) => Value::Literal(Literal::Number(n)),
+            Err(_) => Value::Literal(Literal::Nil),
+        },
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Uppercases a string; any other argument type yields `nil`.
+fn upper(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(Literal::String(s))) => Value::Literal(Literal::String(Rc::from(s.to_uppercase()))),
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Lowercases a string; any other argument type yields `nil`.
+fn lower(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(Literal::String(s))) => Value::Literal(Literal::String(Rc::from(s.to_lowercase()))),
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+pub struct Interpreter {
+    env: Environment,
+    /// A standing handle to the outermost scope, kept alongside `env` so
+    /// a resolver-unresolved name (the `None` depth the `Resolver` leaves
+    /// on anything it can't find in a local scope, i.e. a global) can be
+    /// looked up in one hop instead of walking every enclosing scope
+    /// between wherever `env` currently is and the global one.
+    globals: Environment,
+    call_depth: usize,
+    /// Built-in modules a `use` statement can pull names from, keyed by
+    /// module name. This registry only holds natives added through
+    /// `register_module`; `use` cannot import another rox source file by
+    /// path.
+    modules: HashMap<&'static str, Environment>,
+    on_div_zero: DivZeroPolicy,
+    /// REPL turns recorded by `eval_and_record`, oldest first.
+    history: Vec<HistoryEntry>,
 }
 
-pub type InterpreterResult<'a> = Result<Literal<'a>, InterpreterError>;
+/// One REPL turn: the source text that produced it and the literal it
+/// evaluated to.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub source: String,
+    pub result: Literal,
+}
 
-#[derive(Default)]
-pub struct Interpreter<'a> {
-    env: Environment<'a>,
+/// What `/` and `%` do when the right operand is `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DivZeroPolicy {
+    /// Raise `ErrorKind::DivisionByZero`, same as every other interpreter
+    /// error - the default, since a silent `nil` is easy to miss.
+    #[default]
+    Error,
+    /// Evaluate to `Literal::Nil` instead of raising.
+    Null,
 }
 
-impl<'a> Interpreter<'a> {
-    pub fn evaluate_statement(&mut self, stmt: &'a Stmt<'a>) -> Result<(), InterpreterError> {
+impl Default for Interpreter {
+    fn default() -> Self {
+        let env = Environment::default();
+        let mut inter = Self {
+            globals: env.clone(),
+            env,
+            call_depth: 0,
+            modules: HashMap::new(),
+            on_div_zero: DivZeroPolicy::default(),
+            history: Vec::new(),
+        };
+        inter.register_native("clock", 0, clock);
+        inter.register_native("exit", 1, exit);
+        inter.register_native("input", 0, input);
+        inter.register_native("len", 1, len);
+        inter.register_native("str", 1, str);
+        inter.register_native("num", 1, num);
+        inter.register_native("upper", 1, upper);
+        inter.register_native("lower", 1, lower);
+        crate::stdlib::load(&mut inter);
+        inter
+    }
+}
+
+impl Interpreter {
+    /// Sets what `/` and `%` do on a zero divisor, in place of the
+    /// default `DivZeroPolicy::Error`.
+    pub fn set_div_zero_policy(&mut self, policy: DivZeroPolicy) {
+        self.on_div_zero = policy;
+    }
+
+    /// Evaluates `expr` as one REPL turn: updates the auto-bound `ans`
+    /// global to the result and appends `(source, result)` to
+    /// `history()`, so a later line can refer to `ans` or the session can
+    /// list what's been entered so far.
+    ///
+    /// Takes an already-parsed `expr` rather than raw source text (`source`
+    /// is only kept for the history label): scanning and parsing are the
+    /// caller's job, e.g. `Scanner::scan` followed by `Parser::new` over
+    /// its `canonical_tokens()`, the same steps `main.rs`'s `run` takes.
+    pub fn eval_and_record(
+        &mut self,
+        source: impl Into<String>,
+        expr: &Expr,
+    ) -> Result<Literal, InterpreterError> {
+        let line = expr.line();
+        let value = self
+            .evaluate_expression(expr)?
+            .expect_literal()
+            .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?;
+        self.globals.insert(Rc::from("ans"), Value::Literal(value.clone()));
+        self.history.push(HistoryEntry { source: source.into(), result: value.clone() });
+        Ok(value)
+    }
+
+    /// Every REPL turn recorded so far, oldest first.
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    /// Discards recorded history without touching bindings (`ans`
+    /// included), so a session can reset its transcript while keeping
+    /// its variables.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Exposes a host (Rust) function to scripts under `name`, so
+    /// embedders can extend the interpreter with I/O, math, or anything
+    /// else without the parser needing to know about it. Stored as an
+    /// ordinary binding in `self.env` (the global scope at construction
+    /// time), so calls resolve it through the exact same `get`/scope-walk
+    /// path as a user-declared function - natives and script functions
+    /// share one namespace.
+    pub fn register_native(&mut self, name: &'static str, arity: usize, func: fn(&[Value]) -> Value) {
+        self.env.insert(Rc::from(name), Value::NativeFunction(NativeFunction { name, arity, func }));
+    }
+
+    /// Registers a group of natives under `module`, reachable via
+    /// `use module::name;` (or `use module;` for all of them) instead of
+    /// as bare globals. Embedders that want a namespaced standard library
+    /// surface alongside (or instead of) `register_native`'s flat one use
+    /// this; the two aren't mutually exclusive; a native can live in both.
+    pub fn register_module(&mut self, module: &'static str, entries: &[(&'static str, usize, fn(&[Value]) -> Value)]) {
+        let scope = self.modules.entry(module).or_insert_with(Environment::default);
+        for &(name, arity, func) in entries {
+            scope.insert(Rc::from(name), Value::NativeFunction(NativeFunction { name, arity, func }));
+        }
+    }
+
+    /// Alias kept for callers (the REPL, the file runner) that just want
+    /// to run a single statement against the interpreter's environment. A
+    /// `return` with nothing to catch it (top-level code) is harmless and
+    /// simply ends the statement.
+    pub fn evaluate(&mut self, stmt: &Stmt) -> Result<(), InterpreterError> {
+        match self.evaluate_statement(stmt) {
+            Ok(()) | Err(ControlFlow::Return(_)) => Ok(()),
+            Err(ControlFlow::Error(err)) => Err(err),
+        }
+    }
+
+    /// `use a;` imports every name `a` exports; `use a::b;` imports just
+    /// `b`. Either way the binding lands in `globals` rather than
+    /// whatever scope is current, matching how the resolver treats a
+    /// `use`-introduced name as an ordinary unresolved (global) one.
+    /// Deeper paths (`a::b::c`) aren't supported - modules here are a
+    /// single flat registry, not a tree - and report the same error as
+    /// an unknown module.
+    fn evaluate_use(&mut self, path: &[Rc<str>], line: u64) -> Result<(), InterpreterError> {
+        let unknown = || InterpreterError::new(line, ErrorKind::UnknownModule(path.join("::")));
+        match path {
+            [module] => {
+                let scope = self.modules.get(module.as_ref()).ok_or_else(unknown)?;
+                for (name, value) in scope.entries() {
+                    self.globals.insert(name, value);
+                }
+                Ok(())
+            }
+            [module, name] => {
+                let value = self.modules.get(module.as_ref()).and_then(|scope| scope.get(name)).ok_or_else(unknown)?;
+                self.globals.insert(Rc::clone(name), value);
+                Ok(())
+            }
+            _ => Err(unknown()),
+        }
+    }
+
+    fn evaluate_statement(&mut self, stmt: &Stmt) -> StmtResult {
         match stmt {
             Stmt::Expression(expr) => {
                 self.evaluate_expression(&expr)?;
             }
             Stmt::Print(expr) => {
-                let result = self.evaluate_expression(&expr)?;
+                let line = expr.line();
+                let result = self
+                    .evaluate_expression(&expr)?
+                    .expect_literal()
+                    .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?;
                 println!("{}", result);
             }
             Stmt::Var(var, expr) => {
@@ -30,81 +372,668 @@ impl<'a> Interpreter<'a> {
                     .as_ref()
                     .map(|t| self.evaluate_expression(t))
                     .transpose()?
-                    .unwrap_or(Literal::Nil);
-                self.env.insert(var, result);
+                    .unwrap_or(Value::Literal(Literal::Nil));
+                self.env.insert(var.clone(), result);
+            }
+            Stmt::Function(name, params, body) => {
+                let fun = Function {
+                    name: name.clone(),
+                    params: params.clone(),
+                    body: body.clone(),
+                    closure: self.env.clone(),
+                };
+                self.env.insert(name.lexeme(), Value::Function(fun));
+            }
+            Stmt::Block(stmts) => self.evaluate_block(stmts)?,
+            Stmt::If(condition, then_branch, else_branch) => {
+                let line = condition.line();
+                let truthy = self
+                    .evaluate_expression(condition)?
+                    .expect_literal()
+                    .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?
+                    .is_truthy();
+                if truthy {
+                    self.evaluate_statement(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.evaluate_statement(else_branch)?;
+                }
             }
-            _ => todo!(),
+            Stmt::While(condition, body) => {
+                loop {
+                    let line = condition.line();
+                    let truthy = self
+                        .evaluate_expression(condition)?
+                        .expect_literal()
+                        .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?
+                        .is_truthy();
+                    if !truthy {
+                        break;
+                    }
+                    self.evaluate_statement(body)?;
+                }
+            }
+            Stmt::Loop(body) => loop {
+                self.evaluate_statement(body)?;
+            },
+            Stmt::DoWhile(condition, body) => loop {
+                self.evaluate_statement(body)?;
+                let line = condition.line();
+                let truthy = self
+                    .evaluate_expression(condition)?
+                    .expect_literal()
+                    .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?
+                    .is_truthy();
+                if !truthy {
+                    break;
+                }
+            },
+            Stmt::Return(expr) => {
+                let value = match expr {
+                    Some(expr) => self.evaluate_expression(expr)?,
+                    None => Value::Literal(Literal::Nil),
+                };
+                return Err(ControlFlow::Return(value));
+            }
+            Stmt::Use(path, line) => self.evaluate_use(path, *line).map_err(ControlFlow::Error)?,
         };
         Ok(())
     }
 
-    fn evaluate_expression(&mut self, expr: &'a Expr<'a>) -> InterpreterResult<'a> {
+    fn evaluate_block(&mut self, stmts: &[Stmt]) -> StmtResult {
+        let env = std::mem::take(&mut self.env);
+        self.env = env.child();
+
+        let result = (|| {
+            for stmt in stmts {
+                self.evaluate_statement(stmt)?;
+            }
+            Ok(())
+        })();
+
+        let env = std::mem::take(&mut self.env);
+        self.env = env.pop();
+
+        result
+    }
+
+    fn evaluate_expression(&mut self, expr: &Expr) -> InterpreterResult {
         match expr {
-            Expr::Literal(lit) => Ok(lit.to_owned()),
+            Expr::Literal(lit) => Ok(Value::Literal(lit.to_owned())),
             Expr::Grouping(expr) => self.evaluate_expression(expr),
-            Expr::Unary(op, expr) => self.evaluate_unary(op, expr),
-            Expr::Binary(exprl, op, exprr) => self.evaluate_binary(op, exprl, exprr),
-            Expr::Variable(token) => self
-                .env
-                .get(token.value())
-                .map(|t| t.to_owned())
-                .ok_or(InterpreterError::WrongValue),
+            Expr::Unary(op, expr, line) => self.evaluate_unary(op, expr, *line),
+            Expr::Binary(exprl, op, exprr, line) => self.evaluate_binary(op, exprl, exprr, *line),
+            Expr::Variable(token, depth) => {
+                let found = match depth.get() {
+                    Some(depth) => self.env.get_at(depth, token.value()),
+                    None => self.globals.get(token.value()),
+                };
+                found.ok_or_else(|| {
+                    InterpreterError::new(
+                        token.line(),
+                        ErrorKind::UndefinedVariable(token.value().to_string()),
+                    )
+                })
+            }
+            Expr::Assign(token, value, depth) => {
+                let value = self.evaluate_expression(value)?;
+                let assigned = match depth.get() {
+                    Some(depth) => self.env.assign_at(depth, token.value(), value),
+                    None => self.globals.assign(token.value(), value),
+                };
+                assigned.ok_or_else(|| {
+                    InterpreterError::new(
+                        token.line(),
+                        ErrorKind::UndefinedVariable(token.value().to_string()),
+                    )
+                })
+            }
+            Expr::Call(callee, args, line) => self.evaluate_call(callee, args, *line),
+            Expr::Logical(exprl, op, exprr, line) => self.evaluate_logical(exprl, op, exprr, *line),
+            Expr::Lambda(params, body, line) => {
+                // Lambdas have no name of their own; reuse the same
+                // `Function` representation as `Stmt::Function` under a
+                // placeholder name so `evaluate_call` runs both the same way.
+                let name = Token::new(TokenType::Identifier, "<lambda>", *line);
+                Ok(Value::Function(Function {
+                    name,
+                    params: params.clone(),
+                    body: body.clone(),
+                    closure: self.env.clone(),
+                }))
+            }
+            Expr::Index(target, index, line) => self.evaluate_index(target, index, *line),
+            Expr::Factorial(inner, line) => self.evaluate_factorial(inner, *line),
+            Expr::Get(object, name) => self.evaluate_get(object, name),
+            Expr::Set(object, name, value) => self.evaluate_set(object, name, value),
+        }
+    }
+
+    /// There's no class/instance value in this language yet, so `object`
+    /// can never actually hold a field - this always raises. It exists so
+    /// the grammar and resolver already have a real `Expr::Get`/`Expr::Set`
+    /// shape to dispatch on once an object system lands.
+    fn evaluate_get(&mut self, object: &Expr, name: &Token) -> InterpreterResult {
+        self.evaluate_expression(object)?;
+        Err(InterpreterError::new(name.line(), ErrorKind::TypeError))
+    }
+
+    fn evaluate_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> InterpreterResult {
+        self.evaluate_expression(object)?;
+        self.evaluate_expression(value)?;
+        Err(InterpreterError::new(name.line(), ErrorKind::TypeError))
+    }
+
+    /// The largest integer `f64` still represents exactly; the running
+    /// product is rejected past this point rather than silently losing
+    /// precision.
+    const MAX_EXACT_FACTORIAL_RESULT: f64 = 9_007_199_254_740_992.0; // 2^53
+
+    fn evaluate_factorial(&mut self, expr: &Expr, line: u64) -> InterpreterResult {
+        let type_error = || InterpreterError::new(line, ErrorKind::TypeError);
+        let literal = self.evaluate_expression(expr)?.expect_literal().map_err(|_| type_error())?;
+        let Literal::Number(n) = literal else {
+            return Err(type_error());
+        };
+        if n < 0.0 || n.fract() != 0.0 {
+            return Err(type_error());
+        }
+        let mut result = 1.0;
+        let mut i = 1.0;
+        while i <= n {
+            result *= i;
+            if result > Self::MAX_EXACT_FACTORIAL_RESULT {
+                return Err(InterpreterError::new(line, ErrorKind::Overflow));
+            }
+            i += 1.0;
+        }
+        Ok(Value::Literal(Literal::Number(result)))
+    }
+
+    fn evaluate_index(&mut self, target: &Expr, index: &Expr, line: u64) -> InterpreterResult {
+        let type_error = || InterpreterError::new(line, ErrorKind::TypeError);
+        let target = self.evaluate_expression(target)?.expect_literal().map_err(|_| type_error())?;
+        let index = self.evaluate_expression(index)?.expect_literal().map_err(|_| type_error())?;
+        let (Literal::String(s), Literal::Number(i)) = (target, index) else {
+            return Err(type_error());
+        };
+        if i < 0.0 || i.fract() != 0.0 {
+            return Err(type_error());
+        }
+        let ch = s
+            .chars()
+            .nth(i as usize)
+            .ok_or_else(|| InterpreterError::new(line, ErrorKind::IndexOutOfBounds))?;
+        Ok(Value::Literal(Literal::String(Rc::from(ch.to_string()))))
+    }
+
+    fn evaluate_logical(
+        &mut self,
+        exprl: &Expr,
+        op: &LogicalOperator,
+        exprr: &Expr,
+        line: u64,
+    ) -> InterpreterResult {
+        let left = self.evaluate_expression(exprl)?;
+        let truthy = left
+            .clone()
+            .expect_literal()
+            .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?
+            .is_truthy();
+
+        match (op, truthy) {
+            (LogicalOperator::Or, true) | (LogicalOperator::And, false) => Ok(left),
+            _ => self.evaluate_expression(exprr),
+        }
+    }
+
+    fn evaluate_call(&mut self, callee: &Expr, args: &[Expr], line: u64) -> InterpreterResult {
+        let callee = self.evaluate_expression(callee)?;
+        let mut arguments = Vec::with_capacity(args.len());
+        for arg in args {
+            arguments.push(self.evaluate_expression(arg)?);
+        }
+
+        match callee {
+            Value::NativeFunction(native) => {
+                if native.arity != crate::environment::VARIADIC && arguments.len() != native.arity {
+                    return Err(InterpreterError::new(
+                        line,
+                        ErrorKind::ArityMismatch { expected: native.arity, got: arguments.len() },
+                    ));
+                }
+                Ok((native.func)(&arguments))
+            }
+            Value::Function(fun) => {
+                if arguments.len() != fun.params.len() {
+                    return Err(InterpreterError::new(
+                        line,
+                        ErrorKind::ArityMismatch { expected: fun.params.len(), got: arguments.len() },
+                    ));
+                }
+                if self.call_depth >= MAX_CALL_DEPTH {
+                    return Err(InterpreterError::new(line, ErrorKind::StackOverflow));
+                }
+                // Run the body against a fresh scope enclosed by the
+                // function's captured closure, not whatever environment
+                // happens to be current at the call site, so the
+                // function sees the bindings visible at its definition
+                // (including itself, for recursion) regardless of where
+                // it's called from.
+                let call_env = fun.closure.child();
+                for (param, arg) in fun.params.iter().zip(arguments) {
+                    call_env.insert(param.lexeme(), arg);
+                }
+                let previous = std::mem::replace(&mut self.env, call_env);
+                self.call_depth += 1;
+                let mut result = Value::Literal(Literal::Nil);
+                for stmt in &fun.body {
+                    match self.evaluate_statement(stmt) {
+                        Ok(()) => {}
+                        Err(ControlFlow::Return(value)) => {
+                            result = value;
+                            break;
+                        }
+                        Err(ControlFlow::Error(err)) => {
+                            self.env = previous;
+                            self.call_depth -= 1;
+                            return Err(err);
+                        }
+                    }
+                }
+                self.env = previous;
+                self.call_depth -= 1;
+                Ok(result)
+            }
+            Value::Literal(_) => Err(InterpreterError::new(line, ErrorKind::NotCallable)),
         }
     }
 
     fn evaluate_binary(
         &mut self,
-        op: &'a BinaryOperator,
-        exprl: &'a Expr<'a>,
-        exprr: &'a Expr<'a>,
-    ) -> InterpreterResult<'a> {
-        let litl = self.evaluate_expression(exprl)?;
-        let litr = self.evaluate_expression(exprr)?;
-        match op {
-            BinaryOperator::Less => match (litl, litr) {
-                (Literal::Number(l), Literal::Number(r)) => Ok(Literal::Number(l - r)),
-                _ => todo!(),
-            },
+        op: &BinaryOperator,
+        exprl: &Expr,
+        exprr: &Expr,
+        line: u64,
+    ) -> InterpreterResult {
+        let type_error = || InterpreterError::new(line, ErrorKind::TypeError);
+        let div_zero = |inter: &Self| match inter.on_div_zero {
+            DivZeroPolicy::Error => Err(InterpreterError::new(line, ErrorKind::DivisionByZero)),
+            DivZeroPolicy::Null => Ok(Literal::Nil),
+        };
+        let litl = self.evaluate_expression(exprl)?.expect_literal().map_err(|_| type_error())?;
+        let litr = self.evaluate_expression(exprr)?.expect_literal().map_err(|_| type_error())?;
+        let result = match op {
             BinaryOperator::Plus => match (litl, litr) {
-                (Literal::Number(l), Literal::Number(r)) => Ok(Literal::Number(l + r)),
-                _ => todo!(),
+                (Literal::Number(l), Literal::Number(r)) => Literal::Number(l + r),
+                (Literal::String(l), Literal::String(r)) => {
+                    Literal::String(Rc::from(format!("{l}{r}")))
+                }
+                (l, r) if is_numeric(&l) && is_numeric(&r) => {
+                    let (lre, lim) = as_complex(l);
+                    let (rre, rim) = as_complex(r);
+                    Literal::Complex(lre + rre, lim + rim)
+                }
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::Minus => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => Literal::Number(l - r),
+                (l, r) if is_numeric(&l) && is_numeric(&r) => {
+                    let (lre, lim) = as_complex(l);
+                    let (rre, rim) = as_complex(r);
+                    Literal::Complex(lre - rre, lim - rim)
+                }
+                _ => return Err(type_error()),
             },
             BinaryOperator::Slash => match (litl, litr) {
-                (Literal::Number(l), Literal::Number(r)) => Ok(Literal::Number(l / r)),
-                _ => todo!(),
+                (Literal::Number(l), Literal::Number(r)) => {
+                    if r == 0.0 {
+                        div_zero(self)?
+                    } else {
+                        Literal::Number(l / r)
+                    }
+                }
+                (l, r) if is_numeric(&l) && is_numeric(&r) => {
+                    let (lre, lim) = as_complex(l);
+                    let (rre, rim) = as_complex(r);
+                    let denom = rre * rre + rim * rim;
+                    if denom == 0.0 {
+                        div_zero(self)?
+                    } else {
+                        Literal::Complex(
+                            (lre * rre + lim * rim) / denom,
+                            (lim * rre - lre * rim) / denom,
+                        )
+                    }
+                }
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::Modulo => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => {
+                    if r == 0.0 {
+                        div_zero(self)?
+                    } else {
+                        Literal::Number(l % r)
+                    }
+                }
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::Caret => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => Literal::Number(l.powf(r)),
+                _ => return Err(type_error()),
             },
             BinaryOperator::Star => match (litl, litr) {
-                (Literal::Number(l), Literal::Number(r)) => Ok(Literal::Number(l * r)),
-                _ => todo!(),
+                (Literal::Number(l), Literal::Number(r)) => Literal::Number(l * r),
+                (l, r) if is_numeric(&l) && is_numeric(&r) => {
+                    let (lre, lim) = as_complex(l);
+                    let (rre, rim) = as_complex(r);
+                    Literal::Complex(lre * rre - lim * rim, lre * rim + lim * rre)
+                }
+                _ => return Err(type_error()),
             },
-            _ => {
-                todo!()
-            }
-        }
+            BinaryOperator::Less => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => bool_literal(l < r),
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::LessEqual => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => bool_literal(l <= r),
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::Greater => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => bool_literal(l > r),
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::GreaterEqual => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => bool_literal(l >= r),
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::EqualEqual => bool_literal(litl == litr),
+            BinaryOperator::BangEqual => bool_literal(litl != litr),
+            BinaryOperator::BitAnd => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => {
+                    Literal::Number(((l as i64) & (r as i64)) as f64)
+                }
+                _ => return Err(type_error()),
+            },
+            BinaryOperator::BitOr => match (litl, litr) {
+                (Literal::Number(l), Literal::Number(r)) => {
+                    Literal::Number(((l as i64) | (r as i64)) as f64)
+                }
+                _ => return Err(type_error()),
+            },
+        };
+        Ok(Value::Literal(result))
     }
     fn evaluate_unary(
         &mut self,
-        op: &'a UnaryOperator,
-        expr: &'a Expr<'a>,
-    ) -> InterpreterResult<'a> {
-        let lit = self.evaluate_expression(expr)?;
-        match op {
+        op: &UnaryOperator,
+        expr: &Expr,
+        line: u64,
+    ) -> InterpreterResult {
+        let lit = self
+            .evaluate_expression(expr)?
+            .expect_literal()
+            .map_err(|_| InterpreterError::new(line, ErrorKind::TypeError))?;
+        let result = match op {
             UnaryOperator::Minus => match lit {
-                Literal::Number(v) => Ok(Literal::Number(-v)),
-                _ => todo!(),
+                Literal::Number(v) => Literal::Number(-v),
+                Literal::Complex(re, im) => Literal::Complex(-re, -im),
+                _ => return Err(InterpreterError::new(line, ErrorKind::TypeError)),
             },
             UnaryOperator::Bang => match lit {
-                Literal::False => Ok(Literal::True),
-                Literal::True => Ok(Literal::False),
-                Literal::Number(v) => {
-                    let result = match v {
-                        0.0 => Literal::True,
-                        _ => Literal::False,
-                    };
-                    Ok(result)
-                }
-                _ => todo!(),
+                Literal::False => Literal::True,
+                Literal::True => Literal::False,
+                Literal::Number(v) => match v {
+                    0.0 => Literal::True,
+                    _ => Literal::False,
+                },
+                _ => return Err(InterpreterError::new(line, ErrorKind::TypeError)),
             },
+        };
+        Ok(Value::Literal(result))
+    }
+
+    /// Rewrites `expr` bottom-up into an equivalent but smaller tree:
+    /// folds a `Binary` whose operands are both purely numeric literals
+    /// into a single `Literal`, and applies the identities `x + 0`,
+    /// `x * 1`, and `x * 0` regardless of which side the identity element
+    /// lands on. Division/modulo by a literal `0` are left unfolded
+    /// rather than pre-deciding the `DivisionByZero` error a real
+    /// evaluation would raise.
+    ///
+    /// This simplifies the AST itself, ahead of and independent from
+    /// evaluation - an unbound `Expr::Variable` that is still in the
+    /// simplified tree is still an error, since nothing downstream
+    /// (`evaluate_expression`, the resolver's scope tracking) has a notion
+    /// of a partially-applied expression to evaluate later against. `x + 0`
+    /// simplifies to `x` whether or not `x` is bound; `x * 0` simplifies
+    /// to `0`, dropping `x` and any error evaluating it would raise; `x + y`
+    /// with both unbound still fails at `evaluate_expression` the same way
+    /// it always has.
+    pub fn simplify(expr: &Expr) -> Expr {
+        match expr {
+            Expr::Binary(left, op, right, line) => {
+                let left = Self::simplify(left);
+                let right = Self::simplify(right);
+                if let (Expr::Literal(Literal::Number(l)), Expr::Literal(Literal::Number(r))) =
+                    (&left, &right)
+                {
+                    if let Some(folded) = fold_numeric_binary(*l, op, *r) {
+                        return Expr::literal(folded);
+                    }
+                }
+                if let Some(identity) = apply_identity(&left, op, &right) {
+                    return identity;
+                }
+                Expr::binary(left, op.clone(), right, *line)
+            }
+            Expr::Grouping(inner) => Expr::grouping(Self::simplify(inner)),
+            Expr::Unary(op, inner, line) => Expr::unary(op.clone(), Self::simplify(inner), *line),
+            _ => expr.clone(),
+        }
+    }
+}
+
+/// Identities applied without evaluating the non-identity-element side, so
+/// they apply even when that side is an unbound variable or a value the
+/// operator would reject at evaluation time (`"a" + 0` becomes `"a"`).
+fn apply_identity(left: &Expr, op: &BinaryOperator, right: &Expr) -> Option<Expr> {
+    let is_zero = |e: &Expr| matches!(e, Expr::Literal(Literal::Number(n)) if *n == 0.0);
+    let is_one = |e: &Expr| matches!(e, Expr::Literal(Literal::Number(n)) if *n == 1.0);
+    match op {
+        BinaryOperator::Plus if is_zero(right) => Some(left.clone()),
+        BinaryOperator::Plus if is_zero(left) => Some(right.clone()),
+        BinaryOperator::Star if is_zero(left) || is_zero(right) => {
+            Some(Expr::literal(Literal::Number(0.0)))
         }
+        BinaryOperator::Star if is_one(right) => Some(left.clone()),
+        BinaryOperator::Star if is_one(left) => Some(right.clone()),
+        _ => None,
+    }
+}
+
+/// Constant-folds a binary op over two plain numbers, mirroring
+/// `Interpreter::evaluate_binary`'s numeric arms. Returns `None` for a
+/// case that would only be known to fail at evaluation time (division or
+/// modulo by zero), leaving it as a `Binary` node so that error still
+/// surfaces at the normal time instead of during this ahead-of-time pass.
+fn fold_numeric_binary(l: f64, op: &BinaryOperator, r: f64) -> Option<Literal> {
+    Some(match op {
+        BinaryOperator::Plus => Literal::Number(l + r),
+        BinaryOperator::Minus => Literal::Number(l - r),
+        BinaryOperator::Star => Literal::Number(l * r),
+        BinaryOperator::Slash if r != 0.0 => Literal::Number(l / r),
+        BinaryOperator::Modulo if r != 0.0 => Literal::Number(l % r),
+        BinaryOperator::Caret => Literal::Number(l.powf(r)),
+        BinaryOperator::Greater => bool_literal(l > r),
+        BinaryOperator::GreaterEqual => bool_literal(l >= r),
+        BinaryOperator::Less => bool_literal(l < r),
+        BinaryOperator::LessEqual => bool_literal(l <= r),
+        BinaryOperator::EqualEqual => bool_literal(l == r),
+        BinaryOperator::BangEqual => bool_literal(l != r),
+        BinaryOperator::BitAnd => Literal::Number(((l as i64) & (r as i64)) as f64),
+        BinaryOperator::BitOr => Literal::Number(((l as i64) | (r as i64)) as f64),
+        BinaryOperator::Slash | BinaryOperator::Modulo => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &Expr) -> Result<Literal, InterpreterError> {
+        Interpreter::default().eval_and_record("test", expr)
+    }
+
+    fn num(n: f64) -> Expr {
+        Expr::literal(Literal::Number(n))
+    }
+
+    #[test]
+    fn test_plus_adds_numbers() {
+        let expr = Expr::binary(num(1.0), BinaryOperator::Plus, num(2.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(3.0));
+    }
+
+    #[test]
+    fn test_plus_concatenates_strings() {
+        let expr = Expr::binary(
+            Expr::literal(Literal::String(Rc::from("foo"))),
+            BinaryOperator::Plus,
+            Expr::literal(Literal::String(Rc::from("bar"))),
+            1,
+        );
+        assert_eq!(eval(&expr).unwrap(), Literal::String(Rc::from("foobar")));
+    }
+
+    #[test]
+    fn test_plus_rejects_mixed_number_and_string() {
+        let expr = Expr::binary(
+            num(1.0),
+            BinaryOperator::Plus,
+            Expr::literal(Literal::String(Rc::from("bar"))),
+            1,
+        );
+        assert!(matches!(eval(&expr).unwrap_err().kind, ErrorKind::TypeError));
+    }
+
+    #[test]
+    fn test_minus_subtracts_numbers() {
+        let expr = Expr::binary(num(5.0), BinaryOperator::Minus, num(2.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(3.0));
+    }
+
+    #[test]
+    fn test_star_multiplies_numbers() {
+        let expr = Expr::binary(num(3.0), BinaryOperator::Star, num(4.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(12.0));
+    }
+
+    #[test]
+    fn test_slash_divides_numbers() {
+        let expr = Expr::binary(num(6.0), BinaryOperator::Slash, num(3.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(2.0));
+    }
+
+    #[test]
+    fn test_slash_by_zero_is_division_by_zero_error() {
+        let expr = Expr::binary(num(1.0), BinaryOperator::Slash, num(0.0), 1);
+        assert!(matches!(eval(&expr).unwrap_err().kind, ErrorKind::DivisionByZero));
+    }
+
+    #[test]
+    fn test_modulo_by_zero_is_division_by_zero_error() {
+        let expr = Expr::binary(num(1.0), BinaryOperator::Modulo, num(0.0), 1);
+        assert!(matches!(eval(&expr).unwrap_err().kind, ErrorKind::DivisionByZero));
+    }
+
+    #[test]
+    fn test_caret_raises_to_power() {
+        let expr = Expr::binary(num(2.0), BinaryOperator::Caret, num(10.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(1024.0));
+    }
+
+    #[test]
+    fn test_comparisons_on_numbers() {
+        assert_eq!(
+            eval(&Expr::binary(num(1.0), BinaryOperator::Less, num(2.0), 1)).unwrap(),
+            Literal::True
+        );
+        assert_eq!(
+            eval(&Expr::binary(num(2.0), BinaryOperator::GreaterEqual, num(2.0), 1)).unwrap(),
+            Literal::True
+        );
+        assert_eq!(
+            eval(&Expr::binary(num(2.0), BinaryOperator::Greater, num(2.0), 1)).unwrap(),
+            Literal::False
+        );
+    }
+
+    #[test]
+    fn test_comparisons_reject_non_numbers() {
+        let expr = Expr::binary(
+            Expr::literal(Literal::String(Rc::from("a"))),
+            BinaryOperator::Less,
+            Expr::literal(Literal::String(Rc::from("b"))),
+            1,
+        );
+        assert!(matches!(eval(&expr).unwrap_err().kind, ErrorKind::TypeError));
+    }
+
+    #[test]
+    fn test_equality_across_literal_kinds_never_errors() {
+        let expr = Expr::binary(
+            num(1.0),
+            BinaryOperator::EqualEqual,
+            Expr::literal(Literal::String(Rc::from("1"))),
+            1,
+        );
+        assert_eq!(eval(&expr).unwrap(), Literal::False);
+    }
+
+    #[test]
+    fn test_bitand_and_bitor_truncate_to_integers() {
+        assert_eq!(
+            eval(&Expr::binary(num(6.0), BinaryOperator::BitAnd, num(3.0), 1)).unwrap(),
+            Literal::Number(2.0)
+        );
+        assert_eq!(
+            eval(&Expr::binary(num(6.0), BinaryOperator::BitOr, num(3.0), 1)).unwrap(),
+            Literal::Number(7.0)
+        );
+    }
+
+    #[test]
+    fn test_and_short_circuits_on_falsy_left() {
+        let expr = Expr::logical(
+            Expr::literal(Literal::False),
+            LogicalOperator::And,
+            num(1.0),
+            1,
+        );
+        assert_eq!(eval(&expr).unwrap(), Literal::False);
+    }
+
+    #[test]
+    fn test_or_short_circuits_on_truthy_left() {
+        let expr = Expr::logical(num(1.0), LogicalOperator::Or, Expr::literal(Literal::False), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(1.0));
+    }
+
+    #[test]
+    fn test_and_evaluates_right_when_left_is_truthy() {
+        let expr = Expr::logical(num(1.0), LogicalOperator::And, num(2.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(2.0));
+    }
+
+    #[test]
+    fn test_unary_minus_negates_numbers() {
+        let expr = Expr::unary(UnaryOperator::Minus, num(5.0), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::Number(-5.0));
+    }
+
+    #[test]
+    fn test_unary_bang_negates_truthiness() {
+        let expr = Expr::unary(UnaryOperator::Bang, Expr::literal(Literal::True), 1);
+        assert_eq!(eval(&expr).unwrap(), Literal::False);
     }
 }
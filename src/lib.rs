@@ -0,0 +1,16 @@
+mod bytecode_vm;
+mod environment;
+mod interpreter;
+mod parser;
+mod resolver;
+mod scanner;
+mod stdlib;
+mod syntax_tree;
+mod tokens;
+
+pub use bytecode_vm::{run as run_compiled, RunError as CompiledRunError};
+pub use interpreter::Interpreter;
+pub use parser::Parser;
+pub use resolver::Resolver;
+pub use scanner::{Lexer, Scanner, TokenCategory};
+pub use syntax_tree::optimize_stmts;
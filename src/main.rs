@@ -1,7 +1,134 @@
-use std::io::{self, Write};
-use std::{fmt::Display, path::PathBuf};
+use std::{borrow::Cow, fmt::Display, path::PathBuf};
 
-use yasl::{Interpreter, Parser, Scanner};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+
+use yasl::{optimize_stmts, run_compiled, Interpreter, Parser, Resolver, Scanner, TokenCategory};
+
+const HISTORY_FILE: &str = ".yasl_history";
+
+/// Reserved words the scanner treats as keywords, offered by `ReplHelper`
+/// as completions for whatever identifier-like prefix sits at the cursor.
+const KEYWORDS: &[&str] = &[
+    "and", "class", "else", "false", "fun", "for", "if", "loop", "do", "nil", "or", "print",
+    "return", "super", "this", "true", "var", "while",
+];
+
+/// Drives the REPL's multiline continuation and syntax coloring by
+/// re-scanning the buffer on every keystroke/line. Stateless: everything
+/// it needs comes from the line it's handed.
+struct ReplHelper;
+
+impl Validator for ReplHelper {
+    /// Keeps reading lines (`ValidationResult::Incomplete`) while the
+    /// buffer has unclosed `(`/`{` or its last token isn't a `;`, so a
+    /// multi-line `fun`/`class`/block body can be typed across several
+    /// `readline` calls before `Command::execute` ever sees it.
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let scan = Scanner::new(ctx.input()).scan();
+        if scan.errors().is_some() {
+            return Ok(ValidationResult::Incomplete);
+        }
+        let mut depth: i32 = 0;
+        let mut last_significant = None;
+        for token in scan.tokens() {
+            if token.is_eof() {
+                continue;
+            }
+            if token.is_left_paren_or_brace() {
+                depth += 1;
+            } else if token.is_right_paren_or_brace() {
+                depth -= 1;
+            }
+            last_significant = Some(token);
+        }
+        let incomplete = depth > 0 || matches!(last_significant, Some(token) if !token.is_semicolon());
+        if incomplete {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let scan = Scanner::new(line).scan();
+        let mut out = String::with_capacity(line.len() + 16);
+        let mut cursor = 0;
+        for token in scan.tokens() {
+            if token.is_eof() || token.start() < cursor || token.end() > line.len() {
+                continue;
+            }
+            out.push_str(&line[cursor..token.start()]);
+            let color = match token.category() {
+                TokenCategory::Keyword => Some("\x1b[35m"),
+                TokenCategory::String => Some("\x1b[32m"),
+                TokenCategory::Number => Some("\x1b[36m"),
+                TokenCategory::Operator | TokenCategory::Punctuation => Some("\x1b[33m"),
+                TokenCategory::Identifier | TokenCategory::Eof => None,
+            };
+            match color {
+                Some(code) => {
+                    out.push_str(code);
+                    out.push_str(&line[token.start()..token.end()]);
+                    out.push_str("\x1b[0m");
+                }
+                None => out.push_str(&line[token.start()..token.end()]),
+            }
+            cursor = token.end();
+        }
+        out.push_str(&line[cursor..]);
+        Cow::Owned(out)
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        true
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    fn hint(&self, _line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        None
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    /// Offers `KEYWORDS` completions for the identifier-like word ending
+    /// at `pos`; not a full scan, since completion only ever needs the
+    /// word directly under the cursor, not the whole buffer's tokens.
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+            .map_or(0, |i| i + 1);
+        let prefix = &line[start..pos];
+        if prefix.is_empty() {
+            return Ok((start, Vec::new()));
+        }
+        let candidates = KEYWORDS
+            .iter()
+            .filter(|keyword| keyword.starts_with(prefix))
+            .map(|keyword| Pair { display: keyword.to_string(), replacement: keyword.to_string() })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Helper for ReplHelper {}
 
 enum Command {
     Exit,
@@ -21,29 +148,16 @@ impl Command {
         }
     }
 
-    fn execute(self, inter: &mut Interpreter) {
+    /// Returns `true` only for `Run`, and only when the statement isn't
+    /// finished yet (`Parser::is_incomplete`) — the REPL then knows to
+    /// read another line and retry with the combined buffer instead of
+    /// treating this as a completed (or failed) statement.
+    fn execute(self, inter: &mut Interpreter) -> bool {
         match self {
             Self::Exit => {
                 std::process::exit(0);
             }
-            Self::Run(v) => {
-                let scan = Scanner::new(&v).scan();
-                if let Some(scan_errors) = scan.errors() {
-                    eprintln!("error scanning {:?}", &scan_errors);
-                    return;
-                };
-                let parser = Parser::new(scan.tokens());
-                if let Some(parse_errors) = parser.errors() {
-                    eprintln!("error parsing {:?}", &parse_errors);
-                    return;
-                };
-                let stmts = parser.results();
-                for stmt in stmts {
-                    if let Err(err) = inter.evaluate(stmt) {
-                        eprintln!("error interpreting {:?}", &err);
-                    };
-                }
-            }
+            Self::Run(v) => run(&v, inter),
         }
     }
 }
@@ -58,14 +172,143 @@ impl Display for Command {
     }
 }
 
+/// Scans, parses, resolves, and evaluates `source` against `inter`,
+/// printing errors from any stage as they occur. Returns `true` if the
+/// parser's only complaint is that the token stream ran out
+/// mid-statement, so the caller should read more lines and retry rather
+/// than report failure.
+fn run(source: &str, inter: &mut Interpreter) -> bool {
+    let scan = Scanner::new(source).scan();
+    if let Some(scan_errors) = scan.errors() {
+        eprintln!("error scanning {:?}", &scan_errors);
+        return false;
+    };
+    let parser = Parser::new(scan.canonical_tokens());
+    if parser.is_incomplete() {
+        return true;
+    }
+    if let Some(parse_errors) = parser.errors() {
+        for err in parse_errors {
+            eprintln!("{}", err);
+        }
+        return false;
+    };
+    let stmts = optimize_stmts(parser.results().to_vec());
+    if let Err(err) = Resolver::new().resolve(&stmts) {
+        eprintln!("error resolving {:?}", &err);
+        return false;
+    }
+    for stmt in &stmts {
+        if let Err(err) = inter.evaluate(stmt) {
+            eprintln!("{}", err);
+        };
+    }
+    false
+}
+
+/// Scans, parses, resolves, and evaluates `source` against `inter`, like
+/// `run`, but for running a script file to completion rather than a REPL
+/// line: it reports every runtime error instead of stopping at the first,
+/// and returns the conventional exit code (65 for a scan/parse/resolve
+/// error, 70 for a runtime error, 0 on success) so scripts and test
+/// harnesses can tell success from failure.
+fn run_file(source: &str, inter: &mut Interpreter) -> i32 {
+    let scan = Scanner::new(source).scan();
+    if let Some(scan_errors) = scan.errors() {
+        eprintln!("error scanning {:?}", &scan_errors);
+        return 65;
+    };
+    let parser = Parser::new(scan.canonical_tokens());
+    if let Some(parse_errors) = parser.errors() {
+        for err in parse_errors {
+            eprintln!("{}", err);
+        }
+        return 65;
+    };
+    let stmts = optimize_stmts(parser.results().to_vec());
+    if let Err(err) = Resolver::new().resolve(&stmts) {
+        eprintln!("error resolving {:?}", &err);
+        return 65;
+    }
+    let mut had_runtime_error = false;
+    for stmt in &stmts {
+        if let Err(err) = inter.evaluate(stmt) {
+            eprintln!("{}", err);
+            had_runtime_error = true;
+        };
+    }
+    if had_runtime_error {
+        70
+    } else {
+        0
+    }
+}
+
+/// `run_file`'s counterpart for the bytecode backend.
+fn run_file_compiled(source: &str) -> i32 {
+    let scan = Scanner::new(source).scan();
+    if let Some(scan_errors) = scan.errors() {
+        eprintln!("error scanning {:?}", &scan_errors);
+        return 65;
+    };
+    let parser = Parser::new(scan.canonical_tokens());
+    if let Some(parse_errors) = parser.errors() {
+        for err in parse_errors {
+            eprintln!("{}", err);
+        }
+        return 65;
+    };
+    let stmts = optimize_stmts(parser.results().to_vec());
+    if let Err(err) = Resolver::new().resolve(&stmts) {
+        eprintln!("error resolving {:?}", &err);
+        return 65;
+    }
+    match run_compiled(&stmts) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            70
+        }
+    }
+}
+
 fn interactive() {
     let mut inter = Interpreter::default();
+    let mut rl: Editor<ReplHelper, FileHistory> =
+        Editor::new().expect("failed to start the line editor");
+    rl.set_helper(Some(ReplHelper));
+    let _ = rl.load_history(HISTORY_FILE);
+
     loop {
-        print!("$ ");
-        io::stdout().flush().unwrap(); //The text appears right away without waiting for enter.
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        Command::new(input).execute(&mut inter)
+        let mut buffer = String::new();
+        let mut prompt = "$ ";
+        loop {
+            match rl.readline(prompt) {
+                Ok(line) => {
+                    let _ = rl.add_history_entry(line.as_str());
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                    if Command::new(buffer.clone()).execute(&mut inter) {
+                        prompt = "... ";
+                        continue;
+                    }
+                    break;
+                }
+                // Ctrl+C: discard whatever's been typed so far and start
+                // a fresh statement, matching a normal shell's behavior.
+                Err(ReadlineError::Interrupted) => break,
+                // Ctrl+D: exit the REPL cleanly instead of spinning on
+                // the empty input a closed stdin keeps yielding.
+                Err(ReadlineError::Eof) => {
+                    let _ = rl.save_history(HISTORY_FILE);
+                    return;
+                }
+                Err(err) => {
+                    eprintln!("readline error: {err}");
+                    break;
+                }
+            }
+        }
     }
 }
 
@@ -90,29 +333,56 @@ fn read_and_concatenate_files(paths: &[PathBuf]) -> String {
     content
 }
 
+/// Scans (and, when `-a` is given, parses) `source`, dumping
+/// whichever of the two the caller asked for instead of interpreting it.
+/// Mirrors `run`'s error reporting for the stages it exercises.
+fn dump(source: &str, dump_tokens: bool, dump_ast: bool) {
+    let scan = Scanner::new(source).scan();
+    if let Some(scan_errors) = scan.errors() {
+        eprintln!("error scanning {:?}", &scan_errors);
+        return;
+    };
+    if dump_tokens {
+        for token in scan.tokens() {
+            println!("{}", token);
+        }
+    }
+    if dump_ast {
+        let parser = Parser::new(scan.canonical_tokens());
+        if let Some(parse_errors) = parser.errors() {
+            for err in parse_errors {
+                eprintln!("{}", err);
+            }
+            return;
+        };
+        for stmt in parser.results() {
+            println!("{}", stmt);
+        }
+    }
+}
+
 fn main() {
-    let paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let dump_tokens = args.iter().any(|arg| arg == "-t");
+    let dump_ast = args.iter().any(|arg| arg == "-a");
+    let compiled = args.iter().any(|arg| arg == "-c");
+    let paths: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .map(PathBuf::from)
+        .collect();
 
     if paths.is_empty() {
         interactive();
     } else {
         let input = read_and_concatenate_files(&paths);
-        let mut inter = Interpreter::default();
-        let scan = Scanner::new(&input).scan();
-        if let Some(scan_errors) = scan.errors() {
-            eprintln!("error scanning {:?}", &scan_errors);
-            return;
-        };
-        let parser = Parser::new(scan.tokens());
-        if let Some(parse_errors) = parser.errors() {
-            eprintln!("error parsing {:?}", &parse_errors);
-            return;
-        };
-        let stmts = parser.results();
-        for stmt in stmts {
-            if let Err(err) = inter.evaluate(stmt) {
-                eprintln!("error interpreting {:?}", &err);
-            };
+        if dump_tokens || dump_ast {
+            dump(&input, dump_tokens, dump_ast);
+        } else if compiled {
+            std::process::exit(run_file_compiled(&input));
+        } else {
+            let mut inter = Interpreter::default();
+            std::process::exit(run_file(&input, &mut inter));
         }
     }
 }
@@ -1,16 +1,16 @@
 use crate::{
-    syntax_tree::{Expr, Stmt},
+    syntax_tree::{Expr, InvalidNumber, Literal, Stmt},
     tokens::{Token, TokenType},
 };
 
 #[derive(Default, Debug)]
-pub struct Parser<'a> {
-    results: Vec<Stmt<'a>>,
+pub struct Parser {
+    results: Vec<Stmt>,
     errors: Vec<ParserError>,
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(tokens: Vec<Token<'a>>) -> Self {
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
         let mut results = Vec::with_capacity(tokens.len());
         let mut errors = Vec::new();
 
@@ -23,37 +23,189 @@ impl<'a> Parser<'a> {
         Self { results, errors }
     }
 
-    pub fn results(&self) -> &[Stmt<'a>] {
+    pub fn results(&self) -> &[Stmt] {
         &self.results
     }
+
+    pub fn errors(&self) -> Option<&[ParserError]> {
+        (!self.errors.is_empty()).then_some(&self.errors)
+    }
+
+    /// True if every reported error is just the token stream running out
+    /// mid-statement (an unclosed group, a dangling operator, a `return`
+    /// with no semicolon yet, …). A REPL can use this to tell "the user
+    /// isn't done typing" apart from a genuine syntax error and keep
+    /// reading more lines instead of reporting failure.
+    pub fn is_incomplete(&self) -> bool {
+        !self.errors.is_empty()
+            && self
+                .errors
+                .iter()
+                .all(|err| matches!(err.kind, ParserErrorKind::UnexpectedEof))
+    }
 }
 
+/// A syntax error, with the token it was found at so callers can point a
+/// user at *where* the program went wrong rather than just *that* it did.
 #[derive(Debug)]
-enum ParserError {
-    Missing,
+pub struct ParserError {
+    token: Token,
+    kind: ParserErrorKind,
+}
+
+#[derive(Debug)]
+enum ParserErrorKind {
+    Missing(&'static str),
+    UnexpectedEof,
+    InvalidNumber(String),
+    InvalidAssignmentTarget,
+}
+
+impl ParserError {
+    fn new(token: Token, kind: ParserErrorKind) -> Self {
+        Self { token, kind }
+    }
 }
 
-type ParserExprResult<'a> = Result<Expr<'a>, ParserError>;
-type ParserResult<'a> = Result<Stmt<'a>, ParserError>;
+impl std::fmt::Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match &self.kind {
+            ParserErrorKind::Missing(expected) => format!("expected {expected}"),
+            ParserErrorKind::UnexpectedEof => "unexpected end of input".to_string(),
+            ParserErrorKind::InvalidNumber(lexeme) => format!("'{}' is not a valid number", lexeme),
+            ParserErrorKind::InvalidAssignmentTarget => "invalid assignment target".to_string(),
+        };
+        write!(f, "[line {}] error near '{}': {}", self.token.line(), self.token.value(), message)
+    }
+}
 
-struct ParserIter<'a> {
-    inner: std::iter::Peekable<std::vec::IntoIter<Token<'a>>>,
+type ParserExprResult = Result<Expr, ParserError>;
+type ParserResult = Result<Stmt, ParserError>;
+
+struct ParserIter {
+    inner: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    /// The last token actually consumed, used as the error location when
+    /// the stream runs out entirely instead of yielding a trailing `Eof`.
+    last: Token,
 }
 
-impl<'a> ParserIter<'a> {
-    fn new(tokens: Vec<Token<'a>>) -> Self {
+impl ParserIter {
+    fn new(tokens: Vec<Token>) -> Self {
         Self {
             inner: tokens.into_iter().peekable(),
+            last: Token::eof(0),
+        }
+    }
+
+    /// Consumes the next token if it matches `pred`, recording it as the
+    /// last-seen token so a later "ran out of input" error can still point
+    /// somewhere.
+    fn next_if(&mut self, pred: impl Fn(&Token) -> bool) -> Option<Token> {
+        let token = self.inner.next_if(|t| pred(t));
+        if let Some(token) = &token {
+            self.last = token.clone();
+        }
+        token
+    }
+
+    fn expression(&mut self) -> ParserExprResult {
+        self.assignment()
+    }
+
+    /// assignment -> ( call "." )? IDENTIFIER "=" assignment | pipe ;
+    ///
+    /// Parses the left-hand side as a normal expression first and only
+    /// then checks for `=`, so `a = b = c` resolves right-associatively
+    /// without needing a separate lookahead for "is this an lvalue".
+    fn assignment(&mut self) -> ParserExprResult {
+        let expr = self.pipe()?;
+        if let Some(eq) = self.next_if(|t| t.kind().eq(&TokenType::Equal)) {
+            let value = self.assignment()?;
+            return match expr {
+                Expr::Variable(name, _) => Ok(Expr::assign(name, value)),
+                Expr::Get(object, name) => Ok(Expr::set(*object, name, value)),
+                _ => Err(ParserError::new(eq, ParserErrorKind::InvalidAssignmentTarget)),
+            };
+        }
+        Ok(expr)
+    }
+
+    /// Consumes the next token if it matches `pred`, distinguishing "the
+    /// wrong token is here" (`Missing`, reported with `expected` so the
+    /// message says what should have come next) from "there is no more
+    /// input to look at" (`UnexpectedEof`) so callers can tell a genuine
+    /// syntax error apart from an as-yet-unfinished statement.
+    fn expect(
+        &mut self,
+        pred: impl Fn(&TokenType) -> bool,
+        expected: &'static str,
+    ) -> Result<Token, ParserError> {
+        match self.inner.peek() {
+            Some(t) if pred(t.kind()) => {
+                let token = self.inner.next().expect("peek confirmed a token");
+                self.last = token.clone();
+                Ok(token)
+            }
+            Some(t) if t.kind().eq(&TokenType::Eof) => {
+                Err(ParserError::new(t.clone(), ParserErrorKind::UnexpectedEof))
+            }
+            Some(t) => Err(ParserError::new(t.clone(), ParserErrorKind::Missing(expected))),
+            None => Err(ParserError::new(self.last.clone(), ParserErrorKind::UnexpectedEof)),
         }
     }
 
-    fn expression(&mut self) -> ParserExprResult<'a> {
-        self.equality()
+    /// pipe -> logic_or ( "|>" logic_or )* ;
+    /// `a |> f` desugars to `f(a)`, `a |> f(b)` to `f(a, b)`.
+    fn pipe(&mut self) -> ParserExprResult {
+        let mut expr = self.or()?;
+        while let Some(token) = self.next_if(|t| t.kind().eq(&TokenType::Pipe)) {
+            let target = self.or()?;
+            expr = Self::pipe_into(expr, target, token.line());
+        }
+        Ok(expr)
     }
 
-    /// primary -> NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" ;
-    fn primary(&mut self) -> ParserExprResult<'a> {
-        if let Some(token) = self.inner.next_if(|t| {
+    /// Prepends `piped` as the first argument of a call, wrapping a bare
+    /// callee (`a |> f`) in a fresh single-argument call.
+    fn pipe_into(piped: Expr, target: Expr, line: u64) -> Expr {
+        match target {
+            Expr::Call(callee, mut arguments, call_line) => {
+                arguments.insert(0, piped);
+                Expr::Call(callee, arguments, call_line)
+            }
+            callee => Expr::call(callee, vec![piped], line),
+        }
+    }
+
+    /// logic_or -> logic_and ( "or" logic_and )* ;
+    fn or(&mut self) -> ParserExprResult {
+        let mut expr = self.and()?;
+        while let Some(token) = self.next_if(|t| t.kind().eq(&TokenType::Or)) {
+            expr = Expr::logical(expr, token.kind().into(), self.and()?, token.line())
+        }
+        Ok(expr)
+    }
+
+    /// logic_and -> equality ( "and" equality )* ;
+    fn and(&mut self) -> ParserExprResult {
+        let mut expr = self.equality()?;
+        while let Some(token) = self.next_if(|t| t.kind().eq(&TokenType::And)) {
+            expr = Expr::logical(expr, token.kind().into(), self.equality()?, token.line())
+        }
+        Ok(expr)
+    }
+
+    /// primary -> lambda | boxed_operator | IDENTIFIER | NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" ;
+    fn primary(&mut self) -> ParserExprResult {
+        if let Some(lambda) = self.try_lambda()? {
+            return Ok(lambda);
+        }
+
+        if let Some(boxed) = self.try_boxed_operator()? {
+            return Ok(boxed);
+        }
+
+        if let Some(token) = self.next_if(|t| {
             matches!(
                 t.kind(),
                 TokenType::Nil
@@ -62,63 +214,215 @@ impl<'a> ParserIter<'a> {
                     | TokenType::False
                     | TokenType::True
                     | TokenType::LeftParen
+                    | TokenType::Identifier
             )
         }) {
             return match token.kind() {
                 TokenType::LeftParen => {
                     let expr = self.expression()?;
-                    let _ = self
-                        .inner
-                        .next_if(|t| t.kind().eq(&TokenType::RightParen))
-                        .ok_or(ParserError::Missing)?;
+                    self.expect(|k| k.eq(&TokenType::RightParen), "')' after expression")?;
 
                     Ok(Expr::grouping(expr))
                 }
-                _ => Ok(Expr::literal(token.into())),
+                TokenType::Identifier => Ok(Expr::variable(token)),
+                _ => match Literal::try_from(token.clone()) {
+                    Ok(lit) => Ok(Expr::literal(lit)),
+                    Err(InvalidNumber(lexeme)) => {
+                        Err(ParserError::new(token, ParserErrorKind::InvalidNumber(lexeme)))
+                    }
+                },
             };
         }
-        Err(ParserError::Missing)
+        match self.inner.peek() {
+            Some(t) if t.kind().eq(&TokenType::Eof) => {
+                Err(ParserError::new(t.clone(), ParserErrorKind::UnexpectedEof))
+            }
+            Some(t) => Err(ParserError::new(t.clone(), ParserErrorKind::Missing("an expression"))),
+            None => Err(ParserError::new(self.last.clone(), ParserErrorKind::UnexpectedEof)),
+        }
     }
 
-    /// unary -> ( "!" | "-" ) unary | primary;
-    fn unary(&mut self) -> ParserExprResult<'a> {
-        match self
-            .inner
-            .next_if(|t| matches!(t.kind(), TokenType::Bang | TokenType::Minus))
-        {
-            Some(token) => Ok(Expr::unary(token.kind().into(), self.unary()?)),
-            None => self.primary(),
+    /// lambda -> ( IDENTIFIER | "(" ( IDENTIFIER ( "," IDENTIFIER )* )? ")" ) "->" expression ;
+    ///
+    /// Tries the lambda forms via a cloned lookahead and only commits
+    /// (advancing `self.inner`) once `"->"` is actually found, so a bare
+    /// variable reference or a parenthesized expression falls through to
+    /// the rest of `primary` untouched.
+    fn try_lambda(&mut self) -> Result<Option<Expr>, ParserError> {
+        if matches!(self.inner.peek().map(|t| t.kind()), Some(TokenType::Identifier)) {
+            let mut lookahead = self.inner.clone();
+            let param = lookahead.next().expect("peek confirmed an Identifier token");
+            if lookahead.next_if(|t| t.kind().eq(&TokenType::Arrow)).is_some() {
+                self.inner = lookahead;
+                let line = param.line();
+                let body = self.expression()?;
+                return Ok(Some(Expr::lambda(vec![param], vec![Stmt::Return(Some(body))], line)));
+            }
+            return Ok(None);
         }
+
+        if matches!(self.inner.peek().map(|t| t.kind()), Some(TokenType::LeftParen)) {
+            let mut lookahead = self.inner.clone();
+            let open = lookahead.next().expect("peek confirmed a LeftParen token");
+            let mut params = Vec::new();
+            if !matches!(lookahead.peek().map(|t| t.kind()), Some(TokenType::RightParen)) {
+                loop {
+                    match lookahead.next_if(|t| t.kind().eq(&TokenType::Identifier)) {
+                        Some(param) => params.push(param),
+                        None => return Ok(None),
+                    }
+                    if lookahead.next_if(|t| t.kind().eq(&TokenType::Comma)).is_none() {
+                        break;
+                    }
+                }
+            }
+            if lookahead.next_if(|t| t.kind().eq(&TokenType::RightParen)).is_none() {
+                return Ok(None);
+            }
+            if lookahead.next_if(|t| t.kind().eq(&TokenType::Arrow)).is_none() {
+                return Ok(None);
+            }
+            self.inner = lookahead;
+            let line = open.line();
+            let body = self.expression()?;
+            return Ok(Some(Expr::lambda(params, vec![Stmt::Return(Some(body))], line)));
+        }
+
+        Ok(None)
     }
 
-    /// factor -> unary ( ( "/" | "*" ) unary )* ;
-    fn factor(&mut self) -> ParserExprResult<'a> {
+    /// boxed_operator -> "\" ( "+" | "-" | "*" | "/" | "%" | "^" | "&" | "|"
+    ///                        | ">" | ">=" | "<" | "<=" | "==" | "!=" ) ;
+    ///
+    /// `\+` (or `\*`, `\<`, ...) desugars to `fun(x, y) { return x + y; }`,
+    /// reusing the same `Lambda`/closure path as any other anonymous
+    /// function, so an operator can be passed to a higher-order function
+    /// (`reduce(list, \+)`) without a second evaluation path for it.
+    fn try_boxed_operator(&mut self) -> Result<Option<Expr>, ParserError> {
+        let Some(backslash) = self.next_if(|t| t.kind().eq(&TokenType::Backslash)) else {
+            return Ok(None);
+        };
+        let operator = self.expect(
+            |k| {
+                matches!(
+                    k,
+                    TokenType::Plus
+                        | TokenType::Minus
+                        | TokenType::Star
+                        | TokenType::Slash
+                        | TokenType::Percent
+                        | TokenType::Caret
+                        | TokenType::Ampersand
+                        | TokenType::Bar
+                        | TokenType::Greater
+                        | TokenType::GreaterEqual
+                        | TokenType::Less
+                        | TokenType::LessEqual
+                        | TokenType::EqualEqual
+                        | TokenType::BangEqual
+                )
+            },
+            "a binary operator after '\\'",
+        )?;
+        let line = backslash.line();
+        let x = Token::new(TokenType::Identifier, "x", line);
+        let y = Token::new(TokenType::Identifier, "y", line);
+        let body = Expr::binary(
+            Expr::variable(x.clone()),
+            operator.kind().into(),
+            Expr::variable(y.clone()),
+            line,
+        );
+        Ok(Some(Expr::lambda(vec![x, y], vec![Stmt::Return(Some(body))], line)))
+    }
+
+    /// call -> primary ( ( "(" ( expression ( "," expression )* )? ")" ) | ( "[" expression "]" ) | ( "." IDENTIFIER ) )* ;
+    fn call(&mut self) -> ParserExprResult {
+        let mut expr = self.primary()?;
+        loop {
+            if let Some(paren) = self.next_if(|t| t.kind().eq(&TokenType::LeftParen)) {
+                let mut arguments = Vec::new();
+                if !matches!(self.inner.peek().map(|t| t.kind()), Some(TokenType::RightParen)) {
+                    loop {
+                        arguments.push(self.expression()?);
+                        if self.next_if(|t| t.kind().eq(&TokenType::Comma)).is_none() {
+                            break;
+                        }
+                    }
+                }
+                self.expect(|k| k.eq(&TokenType::RightParen), "')' after arguments")?;
+                expr = Expr::call(expr, arguments, paren.line());
+            } else if let Some(bracket) = self.next_if(|t| t.kind().eq(&TokenType::LeftBracket)) {
+                let index = self.expression()?;
+                self.expect(|k| k.eq(&TokenType::RightBracket), "']' after index")?;
+                expr = Expr::index(expr, index, bracket.line());
+            } else if self.next_if(|t| t.kind().eq(&TokenType::Dot)).is_some() {
+                let name = self.expect(|k| k.eq(&TokenType::Identifier), "a property name after '.'")?;
+                expr = Expr::get(expr, name);
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    /// postfix -> call ( "!" )* ; binds tighter than "^" so `2 ^ 3!`
+    /// is `2 ^ (3!)`, not `(2 ^ 3)!`.
+    fn postfix(&mut self) -> ParserExprResult {
+        let mut expr = self.call()?;
+        while let Some(token) = self.next_if(|t| t.kind().eq(&TokenType::Bang)) {
+            expr = Expr::factorial(expr, token.line());
+        }
+        Ok(expr)
+    }
+
+    /// power -> postfix ( "^" power )* ; right-associative, so `2 ^ 3 ^ 2`
+    /// parses as `2 ^ (3 ^ 2)` rather than `(2 ^ 3) ^ 2`, matching how
+    /// exponentiation is conventionally grouped.
+    fn power(&mut self) -> ParserExprResult {
+        let expr = self.postfix()?;
+        match self.next_if(|t| t.kind().eq(&TokenType::Caret)) {
+            Some(token) => Ok(Expr::binary(expr, token.kind().into(), self.power()?, token.line())),
+            None => Ok(expr),
+        }
+    }
+
+    /// unary -> ( "!" | "-" ) unary | power ; prefix "!"/"-" bind looser
+    /// than "^", so `-2 ^ 2` is `-(2 ^ 2)`, not `(-2) ^ 2`.
+    fn unary(&mut self) -> ParserExprResult {
+        match self.next_if(|t| matches!(t.kind(), TokenType::Bang | TokenType::Minus)) {
+            Some(token) => {
+                let line = token.line();
+                Ok(Expr::unary(token.kind().into(), self.unary()?, line))
+            }
+            None => self.power(),
+        }
+    }
+
+    /// factor -> unary ( ( "/" | "*" | "%" ) unary )* ;
+    fn factor(&mut self) -> ParserExprResult {
         let mut expr = self.unary()?;
-        while let Some(token) = self
-            .inner
-            .next_if(|t| matches!(t.kind(), TokenType::Slash | TokenType::Star))
-        {
-            expr = Expr::binary(expr, token.kind().into(), self.unary()?)
+        while let Some(token) = self.next_if(|t| {
+            matches!(t.kind(), TokenType::Slash | TokenType::Star | TokenType::Percent)
+        }) {
+            expr = Expr::binary(expr, token.kind().into(), self.unary()?, token.line())
         }
         Ok(expr)
     }
 
     /// term -> factor ( ( "-" | "+" ) factor )* ;
-    fn term(&mut self) -> ParserExprResult<'a> {
+    fn term(&mut self) -> ParserExprResult {
         let mut expr = self.factor()?;
-        while let Some(token) = self
-            .inner
-            .next_if(|t| matches!(t.kind(), TokenType::Minus | TokenType::Plus))
-        {
-            expr = Expr::binary(expr, token.kind().into(), self.factor()?)
+        while let Some(token) = self.next_if(|t| matches!(t.kind(), TokenType::Minus | TokenType::Plus)) {
+            expr = Expr::binary(expr, token.kind().into(), self.factor()?, token.line())
         }
         Ok(expr)
     }
 
     /// comparaison -> term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
-    fn comparaison(&mut self) -> ParserExprResult<'a> {
+    fn comparaison(&mut self) -> ParserExprResult {
         let mut expr = self.term()?;
-        while let Some(token) = self.inner.next_if(|t| {
+        while let Some(token) = self.next_if(|t| {
             matches!(
                 t.kind(),
                 TokenType::Greater
@@ -127,19 +431,16 @@ impl<'a> ParserIter<'a> {
                     | TokenType::LessEqual
             )
         }) {
-            expr = Expr::binary(expr, token.kind().into(), self.term()?)
+            expr = Expr::binary(expr, token.kind().into(), self.term()?, token.line())
         }
         Ok(expr)
     }
 
     /// equality -> comparaison ( ( "!=" | "==" ) comparaison )* ;
-    fn equality(&mut self) -> ParserExprResult<'a> {
+    fn equality(&mut self) -> ParserExprResult {
         let mut expr = self.comparaison()?;
-        while let Some(token) = self
-            .inner
-            .next_if(|t| matches!(t.kind(), TokenType::BangEqual | TokenType::EqualEqual))
-        {
-            expr = Expr::binary(expr, token.kind().into(), self.comparaison()?)
+        while let Some(token) = self.next_if(|t| matches!(t.kind(), TokenType::BangEqual | TokenType::EqualEqual)) {
+            expr = Expr::binary(expr, token.kind().into(), self.comparaison()?, token.line())
         }
         Ok(expr)
     }
@@ -158,49 +459,269 @@ impl<'a> ParserIter<'a> {
                     | TokenType::Return
                     | TokenType::Var
                     | TokenType::While
+                    | TokenType::Loop
+                    | TokenType::Do
             ) {
                 return;
             }
         }
     }
 
-    fn statement(&mut self) -> ParserResult<'a> {
-        match self.inner.next_if(|t| t.kind().eq(&TokenType::Print)) {
-            Some(_) => self.print_statement(),
-            None => self.expression_statement(),
+    fn statement(&mut self) -> ParserResult {
+        if self.next_if(|t| t.kind().eq(&TokenType::Fun)).is_some() {
+            return self.function_declaration();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::Use)).is_some() {
+            return self.use_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::Var)).is_some() {
+            return self.var_declaration();
         }
+        if self.next_if(|t| t.kind().eq(&TokenType::Print)).is_some() {
+            return self.print_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::Return)).is_some() {
+            return self.return_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::If)).is_some() {
+            return self.if_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::While)).is_some() {
+            return self.while_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::Loop)).is_some() {
+            return Ok(Stmt::Loop(Box::new(self.statement()?)));
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::Do)).is_some() {
+            return self.do_while_statement();
+        }
+        if self.next_if(|t| t.kind().eq(&TokenType::LeftBrace)).is_some() {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        self.expression_statement()
+    }
+
+    /// block -> "{" statement* "}" ;
+    /// The opening brace is already consumed by the caller.
+    fn block(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut stmts = Vec::new();
+        while !matches!(
+            self.inner.peek().map(|t| t.kind()),
+            Some(TokenType::RightBrace) | Some(TokenType::Eof) | None
+        ) {
+            stmts.push(self.statement()?);
+        }
+        self.expect(|k| k.eq(&TokenType::RightBrace), "'}' after block")?;
+        Ok(stmts)
+    }
+
+    /// `if` -> "if" "(" expression ")" statement ( "else" statement )? ;
+    fn if_statement(&mut self) -> ParserResult {
+        self.expect(|k| k.eq(&TokenType::LeftParen), "'(' after 'if'")?;
+        let condition = self.expression()?;
+        self.expect(|k| k.eq(&TokenType::RightParen), "')' after condition")?;
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = match self.next_if(|t| t.kind().eq(&TokenType::Else)) {
+            Some(_) => Some(Box::new(self.statement()?)),
+            None => None,
+        };
+        Ok(Stmt::If(condition, then_branch, else_branch))
+    }
+
+    /// `while` -> "while" "(" expression ")" statement ;
+    fn while_statement(&mut self) -> ParserResult {
+        self.expect(|k| k.eq(&TokenType::LeftParen), "'(' after 'while'")?;
+        let condition = self.expression()?;
+        self.expect(|k| k.eq(&TokenType::RightParen), "')' after condition")?;
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::While(condition, body))
+    }
+
+    /// `do_while` -> "do" statement "while" "(" expression ")" ";" ;
+    fn do_while_statement(&mut self) -> ParserResult {
+        let body = Box::new(self.statement()?);
+        self.expect(|k| k.eq(&TokenType::While), "'while' after 'do' body")?;
+        self.expect(|k| k.eq(&TokenType::LeftParen), "'(' after 'while'")?;
+        let condition = self.expression()?;
+        self.expect(|k| k.eq(&TokenType::RightParen), "')' after condition")?;
+        self.expect(|k| k.eq(&TokenType::Semicolon), "';' after 'do while' condition")?;
+        Ok(Stmt::DoWhile(condition, body))
+    }
+
+    /// `fun` -> "fun" IDENTIFIER "(" ( IDENTIFIER ( "," IDENTIFIER )* )? ")" block ;
+    fn function_declaration(&mut self) -> ParserResult {
+        let name = self.expect(|k| k.eq(&TokenType::Identifier), "a function name after 'fun'")?;
+        self.expect(|k| k.eq(&TokenType::LeftParen), "'(' after function name")?;
+        let mut params = Vec::new();
+        if !matches!(self.inner.peek().map(|t| t.kind()), Some(TokenType::RightParen)) {
+            loop {
+                params.push(self.expect(|k| k.eq(&TokenType::Identifier), "a parameter name")?);
+                if self.next_if(|t| t.kind().eq(&TokenType::Comma)).is_none() {
+                    break;
+                }
+            }
+        }
+        self.expect(|k| k.eq(&TokenType::RightParen), "')' after parameters")?;
+        self.expect(|k| k.eq(&TokenType::LeftBrace), "'{' before function body")?;
+        let body = self.block()?;
+        Ok(Stmt::Function(name, params, body))
+    }
+
+    /// `use` -> "use" IDENTIFIER ( "::" IDENTIFIER )* ";" ;
+    fn use_statement(&mut self) -> ParserResult {
+        let first = self.expect(|k| k.eq(&TokenType::Identifier), "a module name after 'use'")?;
+        let line = first.line();
+        let mut path = vec![first.lexeme()];
+        while self.next_if(|t| t.kind().eq(&TokenType::ColonColon)).is_some() {
+            path.push(self.expect(|k| k.eq(&TokenType::Identifier), "a name after '::'")?.lexeme());
+        }
+        self.expect(|k| k.eq(&TokenType::Semicolon), "';' after use path")?;
+        Ok(Stmt::Use(path, line))
+    }
+
+    /// `var` -> "var" IDENTIFIER ( "=" expression )? ";" ;
+    fn var_declaration(&mut self) -> ParserResult {
+        let name = self.expect(|k| k.eq(&TokenType::Identifier), "a variable name after 'var'")?;
+        let initializer = match self.next_if(|t| t.kind().eq(&TokenType::Equal)) {
+            Some(_) => Some(self.expression()?),
+            None => None,
+        };
+        self.expect(|k| k.eq(&TokenType::Semicolon), "';' after variable declaration")?;
+        Ok(Stmt::Var(name.lexeme(), initializer))
     }
 
-    fn print_statement(&mut self) -> ParserResult<'a> {
+    fn print_statement(&mut self) -> ParserResult {
         let expr = self.expression()?;
-        self.inner
-            .next_if(|t| t.kind().eq(&TokenType::Semicolon))
-            .ok_or(ParserError::Missing)?;
+        self.expect(|k| k.eq(&TokenType::Semicolon), "';' after value")?;
         Ok(Stmt::Print(expr))
     }
-    fn expression_statement(&mut self) -> ParserResult<'a> {
+
+    /// `return` -> "return" expression? ";" ;
+    fn return_statement(&mut self) -> ParserResult {
+        let value = match self.next_if(|t| t.kind().eq(&TokenType::Semicolon)) {
+            Some(_) => None,
+            None => {
+                let expr = self.expression()?;
+                self.expect(|k| k.eq(&TokenType::Semicolon), "';' after return value")?;
+                Some(expr)
+            }
+        };
+        Ok(Stmt::Return(value))
+    }
+    fn expression_statement(&mut self) -> ParserResult {
         let expr = self.expression()?;
-        self.inner
-            .next_if(|t| t.kind().eq(&TokenType::Semicolon))
-            .ok_or(ParserError::Missing)?;
+        self.expect(|k| k.eq(&TokenType::Semicolon), "';' after expression")?;
         Ok(Stmt::Expression(expr))
     }
 }
 
-impl<'a> Iterator for ParserIter<'a> {
-    type Item = ParserResult<'a>;
+impl Iterator for ParserIter {
+    type Item = ParserResult;
     fn next(&mut self) -> Option<Self::Item> {
         match self.inner.peek() {
             Some(t) => {
                 if t.kind().eq(&TokenType::Eof) {
-                    println!("im eod {:?}", self.inner);
                     self.inner.next()?;
                     return None;
                 };
-                println!("parser is in: {:?}", t);
-                Some(self.statement())
+                let stmt = self.statement();
+                if stmt.is_err() {
+                    self.synchronize();
+                }
+                Some(stmt)
             }
             None => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    /// Parses `source` as a single expression statement and renders it
+    /// back as a fully-parenthesized S-expression (`Expr`'s `Display`),
+    /// so precedence/associativity show up directly in the shape of the
+    /// parens instead of needing to inspect the `Expr` tree by hand.
+    fn parse_expr(source: &str) -> String {
+        let scan = Scanner::new(source).scan();
+        assert!(scan.errors().is_none(), "scan errors: {:?}", scan.errors());
+        let parser = Parser::new(scan.canonical_tokens());
+        assert!(parser.errors().is_none(), "parse errors: {:?}", parser.errors());
+        match &parser.results()[0] {
+            Stmt::Expression(expr) => expr.to_string(),
+            other => panic!("expected an expression statement, got {other}"),
+        }
+    }
+
+    #[test]
+    fn test_caret_binds_tighter_than_star() {
+        assert_eq!(parse_expr("2*3^2;"), "(* 2 (^ 3 2))");
+    }
+
+    #[test]
+    fn test_caret_is_right_associative() {
+        assert_eq!(parse_expr("2^3^2;"), "(^ 2 (^ 3 2))");
+    }
+
+    #[test]
+    fn test_unary_minus_binds_looser_than_caret() {
+        assert_eq!(parse_expr("-2^2;"), "(- (^ 2 2))");
+    }
+
+    #[test]
+    fn test_factorial_binds_tighter_than_plus() {
+        assert_eq!(parse_expr("3!+1;"), "(+ (! 3) 1)");
+    }
+
+    #[test]
+    fn test_factorial_binds_tighter_than_caret() {
+        assert_eq!(parse_expr("2^3!;"), "(^ 2 (! 3))");
+    }
+
+    /// Precedence only matters if it also drives the right numeric
+    /// result once evaluated, not just the right tree shape.
+    fn eval_expr(source: &str) -> Literal {
+        let scan = Scanner::new(source).scan();
+        let parser = Parser::new(scan.canonical_tokens());
+        let stmts = parser.results();
+        crate::resolver::Resolver::new().resolve(stmts).unwrap();
+        let expr = match &stmts[0] {
+            Stmt::Expression(expr) => expr,
+            other => panic!("expected an expression statement, got {other}"),
+        };
+        crate::interpreter::Interpreter::default().eval_and_record(source, expr).unwrap()
+    }
+
+    #[test]
+    fn test_caret_right_associativity_evaluates_correctly() {
+        assert_eq!(eval_expr("2^3^2;"), Literal::Number(512.0));
+    }
+
+    #[test]
+    fn test_unary_minus_looser_than_caret_evaluates_correctly() {
+        assert_eq!(eval_expr("-2^2;"), Literal::Number(-4.0));
+    }
+
+    #[test]
+    fn test_factorial_tighter_than_plus_evaluates_correctly() {
+        assert_eq!(eval_expr("3!+1;"), Literal::Number(7.0));
+    }
+
+    #[test]
+    fn test_factorial_tighter_than_caret_evaluates_correctly() {
+        assert_eq!(eval_expr("2^3!;"), Literal::Number(64.0));
+    }
+
+    #[test]
+    fn test_boxed_operator_desugars_to_a_two_argument_lambda() {
+        assert_eq!(parse_expr("\\+;"), "(lambda (x y) (return (+ x y)))");
+    }
+
+    #[test]
+    fn test_boxed_operator_is_callable_like_a_normal_function() {
+        assert_eq!(eval_expr("\\+(2, 3);"), Literal::Number(5.0));
+        assert_eq!(eval_expr("\\*(2, 3);"), Literal::Number(6.0));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::syntax_tree::{Expr, Stmt};
+
+#[derive(Debug)]
+pub enum ResolverError {
+    /// A local variable's own initializer read the variable before it
+    /// finished initializing, e.g. `var a = a;`.
+    ReadInOwnInitializer(String),
+    /// Two declarations of the same name in one local scope, e.g.
+    /// `{ var a; var a; }`. Shadowing across scopes is fine; this is
+    /// about a single scope binding the same name twice, which is
+    /// almost always a typo rather than intentional.
+    AlreadyDeclared(String),
+    /// A `return` outside of any enclosing `fun`, e.g. at the top level
+    /// or directly inside a `while`/`if` that isn't itself in a function.
+    ReturnOutsideFunction,
+}
+
+/// Walks a parsed statement tree once, before interpretation, recording
+/// for each `Expr::Variable` how many enclosing scopes up its binding
+/// lives. The interpreter then looks variables up by that fixed depth
+/// instead of re-searching the whole environment chain on every access.
+#[derive(Default)]
+pub struct Resolver {
+    /// Each scope maps a name to whether its declaration has finished
+    /// resolving its initializer yet.
+    scopes: Vec<HashMap<String, bool>>,
+    /// How many `fun` bodies we're currently nested inside; a `return`
+    /// resolved while this is `0` is outside of any function.
+    function_depth: usize,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resolve(&mut self, stmts: &[Stmt]) -> Result<(), ResolverError> {
+        for stmt in stmts {
+            self.resolve_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str) -> Result<(), ResolverError> {
+        if let Some(scope) = self.scopes.last_mut() {
+            if scope.contains_key(name) {
+                return Err(ResolverError::AlreadyDeclared(name.to_string()));
+            }
+            scope.insert(name.to_string(), false);
+        }
+        Ok(())
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) -> Result<(), ResolverError> {
+        match stmt {
+            Stmt::Expression(expr) | Stmt::Print(expr) => self.resolve_expr(expr),
+            Stmt::Var(name, initializer) => {
+                self.declare(name)?;
+                if let Some(initializer) = initializer {
+                    self.resolve_expr(initializer)?;
+                }
+                self.define(name);
+                Ok(())
+            }
+            Stmt::Function(name, params, body) => {
+                self.declare(name.value())?;
+                self.define(name.value());
+                self.begin_scope();
+                self.function_depth += 1;
+                for param in params {
+                    self.declare(param.value())?;
+                    self.define(param.value());
+                }
+                let result = self.resolve(body);
+                self.function_depth -= 1;
+                self.end_scope();
+                result
+            }
+            Stmt::Block(stmts) => {
+                self.begin_scope();
+                self.resolve(stmts)?;
+                self.end_scope();
+                Ok(())
+            }
+            Stmt::Return(expr) => {
+                if self.function_depth == 0 {
+                    return Err(ResolverError::ReturnOutsideFunction);
+                }
+                match expr {
+                    Some(expr) => self.resolve_expr(expr),
+                    None => Ok(()),
+                }
+            }
+            Stmt::If(condition, then_branch, else_branch) => {
+                self.resolve_expr(condition)?;
+                self.resolve_stmt(then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.resolve_stmt(else_branch)?;
+                }
+                Ok(())
+            }
+            Stmt::While(condition, body) => {
+                self.resolve_expr(condition)?;
+                self.resolve_stmt(body)
+            }
+            Stmt::Loop(body) => self.resolve_stmt(body),
+            Stmt::DoWhile(condition, body) => {
+                self.resolve_stmt(body)?;
+                self.resolve_expr(condition)
+            }
+            // `use` always lands its bindings in the global scope (see
+            // Interpreter::evaluate_statement), regardless of where the
+            // statement itself appears, so there's nothing local to
+            // declare here - a reference to an imported name resolves
+            // the same way any other global does: unresolved locally,
+            // looked up directly against `globals` at runtime.
+            Stmt::Use(..) => Ok(()),
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) -> Result<(), ResolverError> {
+        match expr {
+            Expr::Literal(_) => Ok(()),
+            Expr::Grouping(inner) | Expr::Unary(_, inner, _) => self.resolve_expr(inner),
+            Expr::Binary(left, _, right, _) | Expr::Logical(left, _, right, _) => {
+                self.resolve_expr(left)?;
+                self.resolve_expr(right)
+            }
+            Expr::Call(callee, args, _) => {
+                self.resolve_expr(callee)?;
+                for arg in args {
+                    self.resolve_expr(arg)?;
+                }
+                Ok(())
+            }
+            Expr::Index(target, index, _) => {
+                self.resolve_expr(target)?;
+                self.resolve_expr(index)
+            }
+            Expr::Factorial(inner, _) => self.resolve_expr(inner),
+            Expr::Get(object, _) => self.resolve_expr(object),
+            Expr::Set(object, _, value) => {
+                self.resolve_expr(object)?;
+                self.resolve_expr(value)
+            }
+            Expr::Lambda(params, body, _) => {
+                self.begin_scope();
+                self.function_depth += 1;
+                for param in params {
+                    self.declare(param.value())?;
+                    self.define(param.value());
+                }
+                let result = self.resolve(body);
+                self.function_depth -= 1;
+                self.end_scope();
+                result
+            }
+            Expr::Variable(token, depth) => {
+                let name = token.value();
+                if let Some(scope) = self.scopes.last() {
+                    if scope.get(name) == Some(&false) {
+                        return Err(ResolverError::ReadInOwnInitializer(name.to_string()));
+                    }
+                }
+                depth.set(self.resolve_local(name));
+                Ok(())
+            }
+            Expr::Assign(token, value, depth) => {
+                self.resolve_expr(value)?;
+                depth.set(self.resolve_local(token.value()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Scans scopes from innermost outward, returning the hop distance
+    /// to the first one that declares `name`, or `None` if it's never
+    /// declared locally (and is therefore assumed global).
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name))
+    }
+}
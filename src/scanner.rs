@@ -1,34 +1,92 @@
 use std::{
-     fmt::Display, iter::{ Peekable}, str::{CharIndices, }
+     borrow::Cow, fmt::Display, iter::{ Peekable}, str::{CharIndices, }, sync::mpsc, thread,
 };
 
+/// Byte-offset span of a token or error within the source string.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    start: usize,
+    end: usize,
+}
+
 #[derive(Debug)]
-enum ScanError {
-    UnexpectedCharacter(u64),
-    TokenMissing(u64)
+pub enum ScanError {
+    UnexpectedCharacter { ch: char, line: u64, span: Span },
+    UnterminatedString { line: u64, span: Span },
+    UnterminatedComment { line: u64, span: Span },
+    InvalidEscape { line: u64, span: Span },
+    MalformedNumber { line: u64, span: Span },
 }
 
 impl Display for ScanError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ScanError::UnexpectedCharacter(line) => {
-                write!(f, "Unexpected character encountered at line {}", line)
+            ScanError::UnexpectedCharacter { ch, line, .. } => {
+                write!(f, "Unexpected character '{}' encountered at line {}", ch, line)
+            },
+            ScanError::UnterminatedString { line, .. } => {
+                write!(f, "Unterminated string literal at line {}", line)
             },
-            ScanError::TokenMissing(line) => {
-                write!(f, "Token missing at line {}", line)
+            ScanError::UnterminatedComment { line, .. } => {
+                write!(f, "Unterminated block comment at line {}", line)
+            },
+            ScanError::InvalidEscape { line, .. } => {
+                write!(f, "Invalid escape sequence at line {}", line)
+            },
+            ScanError::MalformedNumber { line, .. } => {
+                write!(f, "Malformed numeric literal at line {}", line)
             },
         }
     }
 }
 
+impl ScanError {
+    fn span(&self) -> Span {
+        match self {
+            ScanError::UnexpectedCharacter { span, .. }
+            | ScanError::UnterminatedString { span, .. }
+            | ScanError::UnterminatedComment { span, .. }
+            | ScanError::InvalidEscape { span, .. }
+            | ScanError::MalformedNumber { span, .. } => *span,
+        }
+    }
+
+    /// Renders this error as a multi-line diagnostic against `source`: the
+    /// offending source line, a caret/underline under the span, and the
+    /// error's message, in that order.
+    pub fn render(&self, source: &str) -> String {
+        let span = self.span();
+        let line_start = source[..span.start.min(source.len())]
+            .rfind('\n')
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+        let line_end = source[line_start..]
+            .find('\n')
+            .map(|pos| line_start + pos)
+            .unwrap_or(source.len());
+        let source_line = &source[line_start..line_end];
+        let underline_start = span.start.saturating_sub(line_start);
+        let underline_len = span.end.saturating_sub(span.start).max(1);
+        format!(
+            "{}\n{}{}\n{}",
+            source_line,
+            " ".repeat(underline_start),
+            "^".repeat(underline_len),
+            self
+        )
+    }
+}
+
 type ScanResult<T> = Result<T, ScanError>;
 
 #[derive(Debug, PartialEq)]
-enum TokenType {
+pub enum TokenType {
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -36,6 +94,8 @@ enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Caret,
     Bang,
     Equal,
     Greater,
@@ -45,9 +105,14 @@ enum TokenType {
     EqualEqual,
     GreaterEqual,
     LessEqual,
+    Pipe,
+    Ampersand,
+    Bar,
+    Backslash,
 
     Identifier,
-    Number,
+    Int,
+    Float,
     String,
     And,
     Class,
@@ -56,6 +121,8 @@ enum TokenType {
     Fun,
     For,
     If,
+    Loop,
+    Do,
     Nil,
     Or,
     Print,
@@ -69,10 +136,12 @@ enum TokenType {
 }
 
 #[derive(Debug)]
-struct Token<'a> {
+pub struct Token<'a> {
     kind: TokenType,
-    lexem: &'a str,
+    lexem: Cow<'a, str>,
     line: u64,
+    span: Span,
+    column: usize,
 }
 
 impl<'a> PartialEq for Token<'a> {
@@ -81,15 +150,192 @@ impl<'a> PartialEq for Token<'a> {
     }
 }
 
+/// Coarse grouping of `TokenType` for consumers outside this module (e.g.
+/// the REPL's syntax highlighter) that want to color tokens by category
+/// without needing the full, private `TokenType` enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCategory {
+    Keyword,
+    String,
+    Number,
+    Operator,
+    Punctuation,
+    Identifier,
+    Eof,
+}
+
 impl<'a> Token<'a> {
-    pub fn new(kind: TokenType, lexem:  &'a str, line: u64) -> Self {
-        Self { kind, lexem, line }
+    pub fn new(kind: TokenType, lexem: impl Into<Cow<'a, str>>, line: u64, span: Span, column: usize) -> Self {
+        Self { kind, lexem: lexem.into(), line, span, column }
     }
-    fn eof(line: u64) -> Self {
+    fn eof(line: u64, pos: usize, column: usize) -> Self {
         Self {
             kind: TokenType::Eof,
-            lexem: "",
+            lexem: Cow::Borrowed(""),
             line,
+            span: Span { start: pos, end: pos },
+            column,
+        }
+    }
+
+    pub fn lexem(&self) -> &str {
+        &self.lexem
+    }
+
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Byte offset of this token's first (`start`) and one-past-its-last
+    /// (`end`) character within the source it was scanned from, so a
+    /// caller holding that same source can slice out the exact lexeme
+    /// (e.g. to wrap it in color codes) without re-deriving it.
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+
+    /// 1-based column of this token's first character within its line.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.kind == TokenType::Eof
+    }
+
+    pub fn is_semicolon(&self) -> bool {
+        self.kind == TokenType::Semicolon
+    }
+
+    pub fn is_left_paren_or_brace(&self) -> bool {
+        matches!(self.kind, TokenType::LeftParen | TokenType::LeftBrace)
+    }
+
+    pub fn is_right_paren_or_brace(&self) -> bool {
+        matches!(self.kind, TokenType::RightParen | TokenType::RightBrace)
+    }
+
+    /// Converts this token into the canonical `crate::tokens::Token`
+    /// hierarchy `Parser` is built against. The two vocabularies predate
+    /// each other and don't fully line up: `Int`/`Float` both become the
+    /// canonical `Number` (the parser tells them apart, if at all, by
+    /// re-inspecting the lexeme), and this scanner has no rule for `->`,
+    /// `::`, or `use` - those exist only in `tokens::TokenType`/the
+    /// parser grammar, so source using them won't lex correctly through
+    /// this path. That's a pre-existing gap this conversion doesn't
+    /// attempt to close; it only bridges the tokens this scanner does
+    /// produce so `Scanner`'s output can actually reach `Parser::new`.
+    pub fn to_canonical(&self) -> crate::tokens::Token {
+        use crate::tokens::TokenType as Canonical;
+        let kind = match self.kind {
+            TokenType::LeftParen => Canonical::LeftParen,
+            TokenType::RightParen => Canonical::RightParen,
+            TokenType::LeftBrace => Canonical::LeftBrace,
+            TokenType::RightBrace => Canonical::RightBrace,
+            TokenType::LeftBracket => Canonical::LeftBracket,
+            TokenType::RightBracket => Canonical::RightBracket,
+            TokenType::Comma => Canonical::Comma,
+            TokenType::Dot => Canonical::Dot,
+            TokenType::Minus => Canonical::Minus,
+            TokenType::Plus => Canonical::Plus,
+            TokenType::Semicolon => Canonical::Semicolon,
+            TokenType::Slash => Canonical::Slash,
+            TokenType::Star => Canonical::Star,
+            TokenType::Percent => Canonical::Percent,
+            TokenType::Caret => Canonical::Caret,
+            TokenType::Bang => Canonical::Bang,
+            TokenType::Equal => Canonical::Equal,
+            TokenType::Greater => Canonical::Greater,
+            TokenType::Less => Canonical::Less,
+            TokenType::BangEqual => Canonical::BangEqual,
+            TokenType::EqualEqual => Canonical::EqualEqual,
+            TokenType::GreaterEqual => Canonical::GreaterEqual,
+            TokenType::LessEqual => Canonical::LessEqual,
+            TokenType::Pipe => Canonical::Pipe,
+            TokenType::Ampersand => Canonical::Ampersand,
+            TokenType::Bar => Canonical::Bar,
+            TokenType::Backslash => Canonical::Backslash,
+            TokenType::Identifier => Canonical::Identifier,
+            TokenType::Int | TokenType::Float => Canonical::Number,
+            TokenType::String => Canonical::String,
+            TokenType::And => Canonical::And,
+            TokenType::Class => Canonical::Class,
+            TokenType::Else => Canonical::Else,
+            TokenType::False => Canonical::False,
+            TokenType::Fun => Canonical::Fun,
+            TokenType::For => Canonical::For,
+            TokenType::If => Canonical::If,
+            TokenType::Loop => Canonical::Loop,
+            TokenType::Do => Canonical::Do,
+            TokenType::Nil => Canonical::Nil,
+            TokenType::Or => Canonical::Or,
+            TokenType::Print => Canonical::Print,
+            TokenType::Return => Canonical::Return,
+            TokenType::Super => Canonical::Super,
+            TokenType::This => Canonical::This,
+            TokenType::True => Canonical::True,
+            TokenType::Var => Canonical::Var,
+            TokenType::While => Canonical::While,
+            TokenType::Eof => Canonical::Eof,
+        };
+        crate::tokens::Token::new(kind, self.lexem.as_ref(), self.line)
+    }
+
+    pub fn category(&self) -> TokenCategory {
+        match self.kind {
+            TokenType::Eof => TokenCategory::Eof,
+            TokenType::Identifier => TokenCategory::Identifier,
+            TokenType::Int | TokenType::Float => TokenCategory::Number,
+            TokenType::String => TokenCategory::String,
+            TokenType::LeftParen
+            | TokenType::RightParen
+            | TokenType::LeftBrace
+            | TokenType::RightBrace
+            | TokenType::LeftBracket
+            | TokenType::RightBracket
+            | TokenType::Comma
+            | TokenType::Dot
+            | TokenType::Semicolon => TokenCategory::Punctuation,
+            TokenType::And
+            | TokenType::Class
+            | TokenType::Else
+            | TokenType::False
+            | TokenType::Fun
+            | TokenType::For
+            | TokenType::If
+            | TokenType::Loop
+            | TokenType::Do
+            | TokenType::Nil
+            | TokenType::Or
+            | TokenType::Print
+            | TokenType::Return
+            | TokenType::Super
+            | TokenType::This
+            | TokenType::True
+            | TokenType::Var
+            | TokenType::While => TokenCategory::Keyword,
+            TokenType::Minus
+            | TokenType::Plus
+            | TokenType::Slash
+            | TokenType::Star
+            | TokenType::Percent
+            | TokenType::Caret
+            | TokenType::Bang
+            | TokenType::Equal
+            | TokenType::Greater
+            | TokenType::Less
+            | TokenType::BangEqual
+            | TokenType::EqualEqual
+            | TokenType::GreaterEqual
+            | TokenType::LessEqual
+            | TokenType::Pipe
+            | TokenType::Ampersand
+            | TokenType::Bar
+            | TokenType::Backslash => TokenCategory::Operator,
         }
     }
 }
@@ -109,6 +355,9 @@ pub struct Scanner<'a> {
     source: &'a str,
     tokens: Vec<Token<'a>>,
     errors: Vec<ScanError>,
+    /// When set, `scan()` inserts synthetic `Semicolon` tokens so statements
+    /// can be newline-terminated instead of requiring an explicit `;`.
+    asi: bool,
 }
 
 impl<'a> Scanner<'a> {
@@ -118,6 +367,17 @@ impl<'a> Scanner<'a> {
             ..Default::default()
         }
     }
+
+    /// Enables automatic semicolon insertion; the default keeps the
+    /// explicit-semicolon behavior unchanged.
+    pub fn with_asi(mut self) -> Self {
+        self.asi = true;
+        self
+    }
+
+    /// Lexes the whole source eagerly into `self.tokens`/`self.errors`.
+    /// A thin wrapper over the same per-token lexing `lexer()` exposes -
+    /// this just collects it instead of yielding one token per call.
     pub fn scan(mut self) -> Self {
         for token_result in ScanIter::new(self.source) {
             match token_result {
@@ -125,16 +385,181 @@ impl<'a> Scanner<'a> {
                 Err(error) => self.errors.push(error),
             }
         }
+        if self.asi {
+            self.tokens = insert_automatic_semicolons(self.tokens);
+        }
         self
     }
+
+    /// A streaming view over this same source, for callers (a REPL, an
+    /// editor) that want `scan`'s lexing one token at a time instead of
+    /// collected into a `Vec` up front. See `Lexer::next_token`.
+    pub fn lexer(&self) -> Lexer<'a> {
+        Lexer::new(self.source)
+    }
+
+    pub fn tokens(&self) -> &[Token<'a>] {
+        &self.tokens
+    }
+
+    /// This scan's tokens converted into the canonical `crate::tokens::Token`
+    /// hierarchy, i.e. what `Parser::new` actually accepts - see
+    /// `Token::to_canonical` for what that conversion does and doesn't cover.
+    pub fn canonical_tokens(&self) -> Vec<crate::tokens::Token> {
+        self.tokens.iter().map(Token::to_canonical).collect()
+    }
+
+    pub fn errors(&self) -> Option<&[ScanError]> {
+        if self.errors.is_empty() {
+            None
+        } else {
+            Some(&self.errors)
+        }
+    }
 }
 
+/// A token-at-a-time front end over the same lexing logic `Scanner::scan`
+/// runs eagerly, for callers - a REPL, an editor's syntax highlighter -
+/// that want one token per call instead of the whole input lexed up
+/// front. `Scanner::scan` stays the batch convenience built on top of
+/// this.
+pub struct Lexer<'a> {
+    inner: ScanIter<'a>,
+}
 
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            inner: ScanIter::new(source),
+        }
+    }
+
+    /// Yields the next token, including the final `Eof`. Stays resumable
+    /// past end of input: once `Eof` has been produced, further calls
+    /// keep yielding it rather than erroring, so a caller driving this
+    /// one token at a time doesn't need special end-of-stream handling.
+    pub fn next_token(&mut self) -> ScanResult<Token<'a>> {
+        match self.inner.next() {
+            Some(result) => result,
+            None => Ok(Token::eof(
+                self.inner.line,
+                self.inner.source.len(),
+                self.inner.column_at(self.inner.source.len()),
+            )),
+        }
+    }
+}
+
+/// Can this token kind legally end a statement? Used by ASI mode to decide
+/// where a newline stands in for an explicit `;`.
+fn can_end_statement(kind: &TokenType) -> bool {
+    matches!(
+        kind,
+        TokenType::Identifier
+            | TokenType::Int
+            | TokenType::Float
+            | TokenType::String
+            | TokenType::RightParen
+            | TokenType::RightBrace
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Nil
+    )
+}
+
+/// Inserts a synthetic `Semicolon` after any statement-ending token
+/// immediately followed by a newline (collapsing consecutive blank lines
+/// into a single inserted semicolon), and guarantees the stream ends with
+/// `Semicolon, Eof` even when `tokens` has no statement-ending token at all.
+fn insert_automatic_semicolons<'a>(tokens: Vec<Token<'a>>) -> Vec<Token<'a>> {
+    let mut out: Vec<Token<'a>> = Vec::with_capacity(tokens.len() + 1);
+    let mut iter = tokens.into_iter().peekable();
+
+    while let Some(token) = iter.next() {
+        if token.kind == TokenType::Eof {
+            let needs_semicolon = out.last().map(|t| t.kind != TokenType::Semicolon).unwrap_or(true);
+            if needs_semicolon {
+                out.push(Token::new(TokenType::Semicolon, ";", token.line, token.span, token.column));
+            }
+            out.push(token);
+            break;
+        }
+
+        let ends_statement = can_end_statement(&token.kind);
+        let line = token.line;
+        let span = token.span;
+        let column = token.column;
+        out.push(token);
+
+        if ends_statement {
+            let newline_follows = iter.peek().map(|next| next.line > line).unwrap_or(false);
+            if newline_follows {
+                out.push(Token::new(TokenType::Semicolon, ";", line, span, column));
+            }
+        }
+    }
+
+    out
+}
+
+/// One item produced by a `ParallelScanner`'s worker thread: either a
+/// successfully lexed token or a recoverable scan error, kept as separate
+/// variants (rather than a `Result`) so the consumer can `filter_map` over
+/// whichever one it cares about.
+#[derive(Debug)]
+pub enum ScanEvent<'a> {
+    Token(Token<'a>),
+    Error(ScanError),
+}
+
+/// Scans `source` on its own thread and lets the caller pull tokens from a
+/// bounded channel as they become available, so lexing and parsing can
+/// overlap instead of running as two sequential passes over the whole
+/// input. The channel's `capacity` provides back-pressure, keeping memory
+/// flat no matter how large `source` is.
+pub struct ParallelScanner<'a> {
+    receiver: mpsc::Receiver<ScanEvent<'a>>,
+}
+
+impl<'a> ParallelScanner<'a> {
+    pub fn scan<'scope>(
+        scope: &'scope thread::Scope<'scope, 'a>,
+        source: &'a str,
+        capacity: usize,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        scope.spawn(move || {
+            for token_result in ScanIter::new(source) {
+                let event = match token_result {
+                    Ok(token) => ScanEvent::Token(token),
+                    Err(error) => ScanEvent::Error(error),
+                };
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { receiver }
+    }
+}
+
+impl<'a> Iterator for ParallelScanner<'a> {
+    type Item = ScanEvent<'a>;
+
+    /// Blocks until the next token/error is ready, and returns `None` once
+    /// the worker thread has sent its final `Eof` token and hung up.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
 
 struct ScanIter<'a> {
     line: u64,
     current: usize,
     start: usize,
+    /// Byte offset of the first character of the current line, used to
+    /// compute each token's column relative to the last newline.
+    line_start: usize,
     source:  &'a str,
     inner: Peekable<CharIndices<'a>>,
     eof_returned: bool,
@@ -146,12 +571,299 @@ impl<'a> ScanIter<'a> {
             line: 1,
             current: 0,
             start: 0,
+            line_start: 0,
             source,
             inner: source.char_indices().peekable(),
             eof_returned: false,
         }
     }
 
+    fn column_at(&self, pos: usize) -> usize {
+        pos - self.line_start + 1
+    }
+
+}
+
+impl<'a> ScanIter<'a> {
+    fn identifier_or_keyword(&mut self, current_pos: usize) -> Token<'a> {
+        let mut end = current_pos + current_char_len(self.source, current_pos);
+        while let Some((pos, c)) = self.inner.peek().copied() {
+            if c.is_alphanumeric() || c == '_' {
+                self.inner.next();
+                end = pos + c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        let lexem = &self.source[current_pos..end];
+        Token::new(
+            keyword_or_identifier(lexem),
+            lexem,
+            self.line,
+            Span { start: current_pos, end },
+            self.column_at(current_pos),
+        )
+    }
+
+    /// Consumes a run of digits matching `digit_ok` with `_` allowed
+    /// anywhere as an ignored separator. Returns the new `end` offset and
+    /// whether the run was malformed: ending on a trailing `_`, or (when
+    /// `require_nonempty`) containing no digits at all.
+    fn digits(&mut self, digit_ok: fn(char) -> bool, require_nonempty: bool, mut end: usize) -> (usize, bool) {
+        let mut saw_digit = false;
+        let mut trailing_sep = false;
+        while let Some((pos, c)) = self.inner.peek().copied() {
+            if digit_ok(c) {
+                self.inner.next();
+                end = pos + 1;
+                saw_digit = true;
+                trailing_sep = false;
+            } else if c == '_' {
+                self.inner.next();
+                end = pos + 1;
+                trailing_sep = true;
+            } else {
+                break;
+            }
+        }
+        (end, (require_nonempty && !saw_digit) || trailing_sep)
+    }
+
+    fn number(&mut self, current_pos: usize) -> ScanResult<Token<'a>> {
+        fn is_hex(c: char) -> bool {
+            c.is_ascii_hexdigit()
+        }
+        fn is_bin(c: char) -> bool {
+            c == '0' || c == '1'
+        }
+        fn is_oct(c: char) -> bool {
+            ('0'..='7').contains(&c)
+        }
+        fn is_dec(c: char) -> bool {
+            c.is_ascii_digit()
+        }
+
+        let mut end = current_pos + 1;
+        let mut is_float = false;
+        let mut malformed;
+
+        // `0x`/`0b`/`0o` switch into a base-specific digit class and, unlike
+        // plain decimal, never take a fraction/exponent.
+        let base_prefix: Option<fn(char) -> bool> = if self.source.as_bytes()[current_pos] == b'0' {
+            match self.inner.peek().copied() {
+                Some((_, 'x' | 'X')) => Some(is_hex as fn(char) -> bool),
+                Some((_, 'b' | 'B')) => Some(is_bin as fn(char) -> bool),
+                Some((_, 'o' | 'O')) => Some(is_oct as fn(char) -> bool),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if let Some(digit_ok) = base_prefix {
+            let (prefix_pos, _) = self.inner.next().expect("peeked prefix char must exist");
+            end = prefix_pos + 1;
+            let (new_end, bad) = self.digits(digit_ok, true, end);
+            end = new_end;
+            malformed = bad;
+        } else {
+            let (new_end, bad) = self.digits(is_dec as fn(char) -> bool, false, end);
+            end = new_end;
+            malformed = bad;
+
+            if let Some((dot_pos, '.')) = self.inner.peek().copied() {
+                let mut after = self.inner.clone();
+                after.next();
+                if matches!(after.peek(), Some((_, c)) if c.is_ascii_digit()) {
+                    self.inner.next();
+                    is_float = true;
+                    let (new_end, bad) = self.digits(is_dec as fn(char) -> bool, true, dot_pos + 1);
+                    end = new_end;
+                    malformed |= bad;
+                }
+            }
+
+            if let Some((e_pos, 'e' | 'E')) = self.inner.peek().copied() {
+                let mut lookahead = self.inner.clone();
+                lookahead.next();
+                let has_sign = matches!(lookahead.peek(), Some((_, '+' | '-')));
+                if has_sign {
+                    lookahead.next();
+                }
+                if matches!(lookahead.peek(), Some((_, c)) if c.is_ascii_digit()) {
+                    self.inner.next();
+                    is_float = true;
+                    let mut exp_end = e_pos + 1;
+                    if has_sign {
+                        let (sign_pos, _) = self.inner.next().expect("sign already confirmed present");
+                        exp_end = sign_pos + 1;
+                    }
+                    let (new_end, bad) = self.digits(is_dec as fn(char) -> bool, true, exp_end);
+                    end = new_end;
+                    malformed |= bad;
+                }
+            }
+
+            // An `i` suffix (`3i`, `2.5i`) marks an imaginary literal; keep
+            // it in the lexeme so the parser's `Literal::try_from` can tell them
+            // apart from plain numbers.
+            if let Some((pos, 'i')) = self.inner.peek().copied() {
+                self.inner.next();
+                end = pos + 1;
+            }
+        }
+
+        if malformed {
+            return Err(ScanError::MalformedNumber {
+                line: self.line,
+                span: Span { start: current_pos, end },
+            });
+        }
+
+        let kind = if is_float { TokenType::Float } else { TokenType::Int };
+        Ok(Token::new(
+            kind,
+            &self.source[current_pos..end],
+            self.line,
+            Span { start: current_pos, end },
+            self.column_at(current_pos),
+        ))
+    }
+
+    /// Scans a `"..."` literal. The common case (no escapes) stays a
+    /// zero-copy borrow into `source`; as soon as a `\` is seen the tail is
+    /// copied into an owned buffer so the escape can be translated.
+    fn string(&mut self, current_pos: usize) -> ScanResult<Token<'a>> {
+        let content_start = current_pos + 1;
+        // Snapshot the column before scanning the body: a string literal
+        // that embeds a newline advances `self.line_start` past
+        // `current_pos`, so computing the column afterward would underflow.
+        let start_column = self.column_at(current_pos);
+        let mut owned: Option<String> = None;
+        let mut seg_start = content_start;
+
+        loop {
+            let Some((pos, c)) = self.inner.next() else {
+                return Err(ScanError::UnterminatedString {
+                    line: self.line,
+                    span: Span { start: current_pos, end: self.source.len() },
+                });
+            };
+
+            match c {
+                '"' => {
+                    let lexem: Cow<'a, str> = match owned {
+                        Some(mut buf) => {
+                            buf.push_str(&self.source[seg_start..pos]);
+                            Cow::Owned(buf)
+                        }
+                        None => Cow::Borrowed(&self.source[seg_start..pos]),
+                    };
+                    return Ok(Token::new(
+                        TokenType::String,
+                        lexem,
+                        self.line,
+                        Span { start: current_pos, end: pos + 1 },
+                        start_column,
+                    ));
+                }
+                '\n' => {
+                    self.line += 1;
+                    self.line_start = pos + 1;
+                }
+                '\\' => {
+                    match owned.as_mut() {
+                        Some(buf) => buf.push_str(&self.source[seg_start..pos]),
+                        None => owned = Some(self.source[seg_start..pos].to_string()),
+                    }
+                    let Some((_, escaped)) = self.inner.next() else {
+                        return Err(ScanError::UnterminatedString {
+                            line: self.line,
+                            span: Span { start: current_pos, end: self.source.len() },
+                        });
+                    };
+                    match escaped {
+                        'n' => owned.as_mut().unwrap().push('\n'),
+                        't' => owned.as_mut().unwrap().push('\t'),
+                        'r' => owned.as_mut().unwrap().push('\r'),
+                        '\\' => owned.as_mut().unwrap().push('\\'),
+                        '"' => owned.as_mut().unwrap().push('"'),
+                        '0' => owned.as_mut().unwrap().push('\0'),
+                        'u' => {
+                            if self.inner.next_if(|&(_, c)| c == '{').is_none() {
+                                return Err(ScanError::InvalidEscape {
+                                    line: self.line,
+                                    span: Span { start: pos, end: pos + 2 },
+                                });
+                            }
+                            let mut hex = String::new();
+                            let mut close_pos = None;
+                            while let Some((hpos, hc)) = self.inner.peek().copied() {
+                                if hc == '}' {
+                                    self.inner.next();
+                                    close_pos = Some(hpos);
+                                    break;
+                                }
+                                hex.push(hc);
+                                self.inner.next();
+                            }
+                            let decoded = close_pos
+                                .and_then(|_| u32::from_str_radix(&hex, 16).ok())
+                                .and_then(char::from_u32);
+                            match decoded {
+                                Some(ch) => owned.as_mut().unwrap().push(ch),
+                                None => {
+                                    return Err(ScanError::InvalidEscape {
+                                        line: self.line,
+                                        span: Span { start: pos, end: pos + 2 + hex.len() },
+                                    })
+                                }
+                            }
+                        }
+                        _ => {
+                            return Err(ScanError::InvalidEscape {
+                                line: self.line,
+                                span: Span { start: pos, end: pos + 1 + escaped.len_utf8() },
+                            })
+                        }
+                    }
+                    seg_start = match self.inner.peek().copied() {
+                        Some((next_pos, _)) => next_pos,
+                        None => self.source.len(),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn current_char_len(source: &str, pos: usize) -> usize {
+    source[pos..].chars().next().map(|c| c.len_utf8()).unwrap_or(1)
+}
+
+fn keyword_or_identifier(lexem: &str) -> TokenType {
+    match lexem {
+        "and" => TokenType::And,
+        "class" => TokenType::Class,
+        "do" => TokenType::Do,
+        "else" => TokenType::Else,
+        "false" => TokenType::False,
+        "fun" => TokenType::Fun,
+        "for" => TokenType::For,
+        "if" => TokenType::If,
+        "loop" => TokenType::Loop,
+        "nil" => TokenType::Nil,
+        "or" => TokenType::Or,
+        "print" => TokenType::Print,
+        "return" => TokenType::Return,
+        "super" => TokenType::Super,
+        "this" => TokenType::This,
+        "true" => TokenType::True,
+        "var" => TokenType::Var,
+        "while" => TokenType::While,
+        _ => TokenType::Identifier,
+    }
 }
 
 impl<'a> Iterator for ScanIter<'a> {
@@ -162,58 +874,127 @@ impl<'a> Iterator for ScanIter<'a> {
                 return None;
             }
             self.eof_returned = true;
-            return Some(Ok(Token::eof(self.line)));
+            return Some(Ok(Token::eof(self.line, self.source.len(), self.column_at(self.source.len()))));
         };
 
+        if current_char == ' ' || current_char == '\t' || current_char == '\r' {
+            return self.next();
+        }
+
+        if current_char.is_alphabetic() || current_char == '_' {
+            return Some(Ok(self.identifier_or_keyword(current_pos)));
+        }
+
+        if current_char.is_ascii_digit() {
+            return Some(self.number(current_pos));
+        }
+
         let next_char = self.inner.peek().map(|n|n.1);
 
 
         match TokenKinds::from_char(current_char, next_char){
             TokenKinds::SingleChar(token_type) => {
-                return Some(Ok(Token::new(token_type, &self.source[current_pos..current_pos + 1], self.line)));
+                Some(Ok(Token::new(
+                    token_type,
+                    &self.source[current_pos..current_pos + 1],
+                    self.line,
+                    Span { start: current_pos, end: current_pos + 1 },
+                    self.column_at(current_pos),
+                )))
             },
             TokenKinds::DoubleChar(token_type) => {
+                // `from_char` only returns `DoubleChar` after peeking the
+                // second character, so the `next()` below always succeeds -
+                // nothing between the peek and this call can consume it.
                 let Some((next_pos, _)) = self.inner.next() else {
-                    return Some(Err(ScanError::TokenMissing(self.line)));
+                    unreachable!("DoubleChar's second character was already confirmed by peek");
                 };
-                return Some(Ok(Token::new(token_type, &self.source[current_pos..next_pos + 1], self.line)));
+                Some(Ok(Token::new(
+                    token_type,
+                    &self.source[current_pos..next_pos + 1],
+                    self.line,
+                    Span { start: current_pos, end: next_pos + 1 },
+                    self.column_at(current_pos),
+                )))
             },
             TokenKinds::Comment => {
-                while let Some((_, c)) = self.inner.next() {
+                while let Some((pos, c)) = self.inner.next() {
                     if c == '\n' {
                         self.line += 1;
+                        self.line_start = pos + 1;
                         return self.next();
                     }
                 }
+                self.next()
+            },
+            TokenKinds::BlockComment => {
+                let mut depth = 1;
+                while let Some((pos, c)) = self.inner.next() {
+                    if c == '\n' {
+                        self.line += 1;
+                        self.line_start = pos + 1;
+                    } else if c == '/' && matches!(self.inner.peek(), Some((_, '*'))) {
+                        self.inner.next();
+                        depth += 1;
+                    } else if c == '*' && matches!(self.inner.peek(), Some((_, '/'))) {
+                        self.inner.next();
+                        depth -= 1;
+                        if depth == 0 {
+                            return self.next();
+                        }
+                    }
+                }
+                Some(Err(ScanError::UnterminatedComment {
+                    line: self.line,
+                    span: Span { start: current_pos, end: self.source.len() },
+                }))
             },
             TokenKinds::NewLine => {
                 self.line += 1;
-                return self.next();
+                self.line_start = current_pos + 1;
+                self.next()
             }
-            TokenKinds::String =>{
-                while let Some((next_pos, c)) = self.inner.next() {
-                    if c == '\n' {
-                        self.line += 1;
-                    } else if c== '"' {
-                        //NOTE: we remove the quotes from the string
-                        let lexem = &self.source[current_pos + 1..next_pos];
-                        return Some(Ok(Token::new(TokenType::String, lexem, self.line)));
+            TokenKinds::String => Some(self.string(current_pos)),
+            TokenKinds::Unknown => {
+                // Resynchronize past the bad byte(s) instead of stopping the
+                // whole scan: swallow characters up to the next whitespace
+                // or delimiter so later, valid tokens still get emitted.
+                let mut end = current_pos + current_char.len_utf8();
+                while let Some((pos, c)) = self.inner.peek().copied() {
+                    if c.is_whitespace() || is_resync_delimiter(c) {
+                        break;
                     }
+                    self.inner.next();
+                    end = pos + c.len_utf8();
                 }
+                Some(Err(ScanError::UnexpectedCharacter {
+                    ch: current_char,
+                    line: self.line,
+                    span: Span { start: current_pos, end },
+                }))
             },
-        };
-
-
-        Some(Err(ScanError::UnexpectedCharacter(self.line)))
+        }
     }
 }
 
+/// Characters that can safely start a fresh token, used to cut a
+/// resynchronization run short once the scanner is back on solid ground.
+fn is_resync_delimiter(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')' | '{' | '}' | '[' | ']' | ',' | ';' | '"' | '\'' | '.' | '+' | '-' | '*' | '/'
+            | '%' | '^' | '|' | '&' | '\\'
+    )
+}
+
 enum TokenKinds {
     SingleChar(TokenType),
     DoubleChar(TokenType),
     Comment,
+    BlockComment,
     NewLine,
-    String
+    String,
+    Unknown,
 }
 
 impl TokenKinds {
@@ -223,25 +1004,34 @@ impl TokenKinds {
             (')', _) => Self::SingleChar(TokenType::RightParen),
             ('{', _) => Self::SingleChar(TokenType::LeftBrace),
             ('}', _) => Self::SingleChar(TokenType::RightBrace),
+            ('[', _) => Self::SingleChar(TokenType::LeftBracket),
+            (']', _) => Self::SingleChar(TokenType::RightBracket),
             (',', _) => Self::SingleChar(TokenType::Comma),
             ('.', _) => Self::SingleChar(TokenType::Dot),
             ('-', _) => Self::SingleChar(TokenType::Minus),
             ('+', _) => Self::SingleChar(TokenType::Plus),
             (';', _) => Self::SingleChar(TokenType::Semicolon),
             ('*', _) => Self::SingleChar(TokenType::Star),
+            ('%', _) => Self::SingleChar(TokenType::Percent),
+            ('^', _) => Self::SingleChar(TokenType::Caret),
             ('!', Some('=')) => Self::DoubleChar(TokenType::BangEqual),
             ('=', Some('=')) => Self::DoubleChar(TokenType::EqualEqual),
             ('>', Some('=')) => Self::DoubleChar(TokenType::GreaterEqual),
             ('<', Some('=')) => Self::DoubleChar(TokenType::LessEqual),
+            ('|', Some('>')) => Self::DoubleChar(TokenType::Pipe),
+            ('|', _) => Self::SingleChar(TokenType::Bar),
+            ('&', _) => Self::SingleChar(TokenType::Ampersand),
+            ('\\', _) => Self::SingleChar(TokenType::Backslash),
             ('!', _) => Self::SingleChar(TokenType::Bang),
             ('=', _) => Self::SingleChar(TokenType::Equal),
             ('>', _) => Self::SingleChar(TokenType::Greater),
             ('<', _) => Self::SingleChar(TokenType::Less),
             ('/', Some('/')) => Self::Comment,
+            ('/', Some('*')) => Self::BlockComment,
             ('/', _) => Self::SingleChar(TokenType::Slash),
             ('\n', _) => Self::NewLine,
             ('"', _) => Self::String,
-            _=> todo!("Handle more token types or errors: {} and {:?}", c, next_c),
+            _ => Self::Unknown,
         }
     }
 }
@@ -251,22 +1041,27 @@ impl TokenKinds {
 mod tests {
     use super::*;
 
+    /// `Token::eq` only compares `kind`/`lexem`/`line`, so tests don't need
+    /// to work out real spans/columns by hand.
+    fn tok<'a>(kind: TokenType, lexem: &'a str, line: u64) -> Token<'a> {
+        Token::new(kind, lexem, line, Span { start: 0, end: 0 }, 0)
+    }
 
     #[test]
     fn test_single_character_tokens() {
         let source = r#"(){}.,-+;*"#;
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::LeftBrace, "{", 1),
-            Token::new(TokenType::RightBrace, "}", 1),
-            Token::new(TokenType::Dot, ".", 1),
-            Token::new(TokenType::Comma, ",", 1),
-            Token::new(TokenType::Minus, "-", 1),
-            Token::new(TokenType::Plus, "+", 1),
-            Token::new(TokenType::Semicolon, ";", 1),
-            Token::new(TokenType::Star, "*", 1),
-            Token::new(TokenType::Eof, "", 1),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::LeftBrace, "{", 1),
+            tok(TokenType::RightBrace, "}", 1),
+            tok(TokenType::Dot, ".", 1),
+            tok(TokenType::Comma, ",", 1),
+            tok(TokenType::Minus, "-", 1),
+            tok(TokenType::Plus, "+", 1),
+            tok(TokenType::Semicolon, ";", 1),
+            tok(TokenType::Star, "*", 1),
+            tok(TokenType::Eof, "", 1),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -276,17 +1071,17 @@ mod tests {
     fn test_single_character_tokens_multiline() {
         let source = "(){}.,\n-+;*";
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::LeftBrace, "{", 1),
-            Token::new(TokenType::RightBrace, "}", 1),
-            Token::new(TokenType::Dot, ".", 1),
-            Token::new(TokenType::Comma, ",", 1),
-            Token::new(TokenType::Minus, "-", 2),
-            Token::new(TokenType::Plus, "+", 2),
-            Token::new(TokenType::Semicolon, ";", 2),
-            Token::new(TokenType::Star, "*", 2),
-            Token::new(TokenType::Eof, "", 2),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::LeftBrace, "{", 1),
+            tok(TokenType::RightBrace, "}", 1),
+            tok(TokenType::Dot, ".", 1),
+            tok(TokenType::Comma, ",", 1),
+            tok(TokenType::Minus, "-", 2),
+            tok(TokenType::Plus, "+", 2),
+            tok(TokenType::Semicolon, ";", 2),
+            tok(TokenType::Star, "*", 2),
+            tok(TokenType::Eof, "", 2),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -296,9 +1091,9 @@ mod tests {
     fn test_simple_characters_tokens_with_comment() {
         let source = r#"()//!=.==>=*"#;
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::Eof, "", 1),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::Eof, "", 1),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -308,11 +1103,11 @@ mod tests {
     fn test_simple_characters_tokens_with_comment_and_new_line() {
         let source = "()//!=.==>\n=*";
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::Equal, "=", 2),
-            Token::new(TokenType::Star, "*", 2),
-            Token::new(TokenType::Eof, "", 2),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::Equal, "=", 2),
+            tok(TokenType::Star, "*", 2),
+            tok(TokenType::Eof, "", 2),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -322,14 +1117,14 @@ mod tests {
     fn test_double_character_tokens() {
         let source = r#"()!=.==>=/"#;
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::BangEqual, "!=", 1),
-            Token::new(TokenType::Dot, ".", 1),
-            Token::new(TokenType::EqualEqual, "==", 1),
-            Token::new(TokenType::GreaterEqual, ">=", 1),
-            Token::new(TokenType::Slash, "/", 1),
-            Token::new(TokenType::Eof, "", 1),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::BangEqual, "!=", 1),
+            tok(TokenType::Dot, ".", 1),
+            tok(TokenType::EqualEqual, "==", 1),
+            tok(TokenType::GreaterEqual, ">=", 1),
+            tok(TokenType::Slash, "/", 1),
+            tok(TokenType::Eof, "", 1),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -339,10 +1134,10 @@ mod tests {
     fn test_string_tokens() {
         let source = "()\"hey, como\"";
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::String, "hey, como", 1),
-            Token::new(TokenType::Eof, "", 1),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::String, "hey, como", 1),
+            tok(TokenType::Eof, "", 1),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -352,10 +1147,191 @@ mod tests {
     fn test_string_tokens_new_line() {
         let source = "()\"hey,\n como\"";
         let expected_tokens = vec![
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::String, "hey,\n como", 2),
-            Token::new(TokenType::Eof, "", 2),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::String, "hey,\n como", 2),
+            tok(TokenType::Eof, "", 2),
+        ];
+        let scanner = Scanner::new(source);
+        assert_eq!(scanner.scan().tokens, expected_tokens);
+    }
+
+    #[test]
+    fn test_string_escape_sequences() {
+        let source = r#""line1\nline2\tend""#;
+        let expected_tokens = vec![
+            tok(TokenType::String, "line1\nline2\tend", 1),
+            tok(TokenType::Eof, "", 1),
+        ];
+        let scanner = Scanner::new(source);
+        assert_eq!(scanner.scan().tokens, expected_tokens);
+    }
+
+    #[test]
+    fn test_string_escaped_quote_and_unicode() {
+        let source = r#""say \"hi\" \u{1F600}""#;
+        let expected_tokens = vec![
+            tok(TokenType::String, "say \"hi\" \u{1F600}", 1),
+            tok(TokenType::Eof, "", 1),
+        ];
+        let scanner = Scanner::new(source);
+        assert_eq!(scanner.scan().tokens, expected_tokens);
+    }
+
+    #[test]
+    fn test_extended_numeric_literals() {
+        let source = "0xFF 0b101 0o17 1_000 6.022e23 3.14";
+        let expected_tokens = vec![
+            tok(TokenType::Int, "0xFF", 1),
+            tok(TokenType::Int, "0b101", 1),
+            tok(TokenType::Int, "0o17", 1),
+            tok(TokenType::Int, "1_000", 1),
+            tok(TokenType::Float, "6.022e23", 1),
+            tok(TokenType::Float, "3.14", 1),
+            tok(TokenType::Eof, "", 1),
+        ];
+        let scanner = Scanner::new(source);
+        assert_eq!(scanner.scan().tokens, expected_tokens);
+    }
+
+    #[test]
+    fn test_malformed_numeric_literal_is_a_scan_error() {
+        let scanner = Scanner::new("0x");
+        let scanned = scanner.scan();
+        assert_eq!(scanned.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_trailing_digit_separator_is_malformed() {
+        let scanner = Scanner::new("1_");
+        let scanned = scanner.scan();
+        assert_eq!(scanned.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_unexpected_character_recovers_and_keeps_scanning() {
+        let source = "1 + @ + 2";
+        let scanned = Scanner::new(source).scan();
+        assert_eq!(scanned.errors.len(), 1);
+        assert_eq!(
+            scanned.tokens,
+            vec![
+                tok(TokenType::Int, "1", 1),
+                tok(TokenType::Plus, "+", 1),
+                tok(TokenType::Plus, "+", 1),
+                tok(TokenType::Int, "2", 1),
+                tok(TokenType::Eof, "", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_lexical_errors_are_all_collected() {
+        let source = "@ 0x $ 1_";
+        let scanned = Scanner::new(source).scan();
+        assert_eq!(scanned.errors.len(), 4);
+    }
+
+    #[test]
+    fn test_asi_inserts_semicolon_after_newline() {
+        let source = "a\nb";
+        let scanned = Scanner::new(source).with_asi().scan();
+        assert_eq!(
+            scanned.tokens,
+            vec![
+                tok(TokenType::Identifier, "a", 1),
+                tok(TokenType::Semicolon, ";", 1),
+                tok(TokenType::Identifier, "b", 2),
+                tok(TokenType::Semicolon, ";", 2),
+                tok(TokenType::Eof, "", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_asi_collapses_blank_lines_into_one_semicolon() {
+        let source = "a\n\n\nb";
+        let scanned = Scanner::new(source).with_asi().scan();
+        assert_eq!(
+            scanned.tokens,
+            vec![
+                tok(TokenType::Identifier, "a", 1),
+                tok(TokenType::Semicolon, ";", 1),
+                tok(TokenType::Identifier, "b", 4),
+                tok(TokenType::Semicolon, ";", 4),
+                tok(TokenType::Eof, "", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_asi_does_not_duplicate_explicit_semicolon() {
+        let source = "a;\nb";
+        let scanned = Scanner::new(source).with_asi().scan();
+        assert_eq!(
+            scanned.tokens,
+            vec![
+                tok(TokenType::Identifier, "a", 1),
+                tok(TokenType::Semicolon, ";", 1),
+                tok(TokenType::Identifier, "b", 2),
+                tok(TokenType::Semicolon, ";", 2),
+                tok(TokenType::Eof, "", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_asi_on_empty_input_still_ends_with_semicolon_eof() {
+        let scanned = Scanner::new("").with_asi().scan();
+        assert_eq!(
+            scanned.tokens,
+            vec![tok(TokenType::Semicolon, ";", 1), tok(TokenType::Eof, "", 1)]
+        );
+    }
+
+    #[test]
+    fn test_without_asi_no_semicolons_are_inserted() {
+        let source = "a\nb";
+        let scanned = Scanner::new(source).scan();
+        assert_eq!(
+            scanned.tokens,
+            vec![
+                tok(TokenType::Identifier, "a", 1),
+                tok(TokenType::Identifier, "b", 2),
+                tok(TokenType::Eof, "", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parallel_scanner_yields_tokens_from_worker_thread() {
+        let source = "1 + 2";
+        let tokens = std::thread::scope(|scope| {
+            ParallelScanner::scan(scope, source, 4)
+                .filter_map(|event| match event {
+                    ScanEvent::Token(token) => Some(token),
+                    ScanEvent::Error(_) => None,
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            tokens,
+            vec![
+                tok(TokenType::Int, "1", 1),
+                tok(TokenType::Plus, "+", 1),
+                tok(TokenType::Int, "2", 1),
+                tok(TokenType::Eof, "", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_block_comments() {
+        let source = "( /* outer /* inner */ still comment */ )";
+        let expected_tokens = vec![
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::Eof, "", 1),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -368,24 +1344,24 @@ mod tests {
         return nil;
     }"#;
         let expected_tokens = vec![
-            Token::new(TokenType::Fun, "fun", 1),
-            Token::new(TokenType::Identifier, "greet", 1),
-            Token::new(TokenType::LeftParen, "(", 1),
-            Token::new(TokenType::Identifier, "name", 1),
-            Token::new(TokenType::RightParen, ")", 1),
-            Token::new(TokenType::LeftBrace, "{", 1),
-            Token::new(TokenType::Print, "print", 2),
-            Token::new(TokenType::String, "\"Hello, \"", 2),
-            Token::new(TokenType::Plus, "+", 2),
-            Token::new(TokenType::Identifier, "name", 2),
-            Token::new(TokenType::Plus, "+", 2),
-            Token::new(TokenType::String, "\"!\"", 2),
-            Token::new(TokenType::Semicolon, ";", 2),
-            Token::new(TokenType::Return, "return", 3),
-            Token::new(TokenType::Nil, "nil", 3),
-            Token::new(TokenType::Semicolon, ";", 3),
-            Token::new(TokenType::RightBrace, "}", 4),
-            Token::new(TokenType::Eof, "", 4),
+            tok(TokenType::Fun, "fun", 1),
+            tok(TokenType::Identifier, "greet", 1),
+            tok(TokenType::LeftParen, "(", 1),
+            tok(TokenType::Identifier, "name", 1),
+            tok(TokenType::RightParen, ")", 1),
+            tok(TokenType::LeftBrace, "{", 1),
+            tok(TokenType::Print, "print", 2),
+            tok(TokenType::String, "Hello, ", 2),
+            tok(TokenType::Plus, "+", 2),
+            tok(TokenType::Identifier, "name", 2),
+            tok(TokenType::Plus, "+", 2),
+            tok(TokenType::String, "!", 2),
+            tok(TokenType::Semicolon, ";", 2),
+            tok(TokenType::Return, "return", 3),
+            tok(TokenType::Nil, "nil", 3),
+            tok(TokenType::Semicolon, ";", 3),
+            tok(TokenType::RightBrace, "}", 4),
+            tok(TokenType::Eof, "", 4),
         ];
         let scanner = Scanner::new(source);
         assert_eq!(scanner.scan().tokens, expected_tokens);
@@ -0,0 +1,121 @@
+//! Math builtins, registered in bulk at interpreter startup rather than
+//! one `register_native` call per function scattered through
+//! `Interpreter::default`. Each function follows the same shape as the
+//! built-ins already living in `interpreter.rs` (`fn(&[Value]) -> Value`,
+//! non-numeric input yielding `nil` instead of panicking), so they plug
+//! into `Interpreter::register_native` unchanged.
+
+use crate::{
+    environment::{Value, VARIADIC},
+    interpreter::Interpreter,
+    syntax_tree::Literal,
+};
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Literal(Literal::Number(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+fn sqrt(args: &[Value]) -> Value {
+    match args.first().and_then(as_number) {
+        Some(n) => Value::Literal(Literal::Number(n.sqrt())),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn floor(args: &[Value]) -> Value {
+    match args.first().and_then(as_number) {
+        Some(n) => Value::Literal(Literal::Number(n.floor())),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn abs(args: &[Value]) -> Value {
+    match args.first().and_then(as_number) {
+        Some(n) => Value::Literal(Literal::Number(n.abs())),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn pow(args: &[Value]) -> Value {
+    match (args.first().and_then(as_number), args.get(1).and_then(as_number)) {
+        (Some(base), Some(exponent)) => Value::Literal(Literal::Number(base.powf(exponent))),
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+fn ceil(args: &[Value]) -> Value {
+    match args.first().and_then(as_number) {
+        Some(n) => Value::Literal(Literal::Number(n.ceil())),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Shared by `min`/`max`/`sum`: `nil` unless every argument is a number.
+fn numbers(args: &[Value]) -> Option<Vec<f64>> {
+    args.iter().map(as_number).collect()
+}
+
+fn min(args: &[Value]) -> Value {
+    match numbers(args).and_then(|ns| ns.into_iter().reduce(f64::min)) {
+        Some(n) => Value::Literal(Literal::Number(n)),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn max(args: &[Value]) -> Value {
+    match numbers(args).and_then(|ns| ns.into_iter().reduce(f64::max)) {
+        Some(n) => Value::Literal(Literal::Number(n)),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn sum(args: &[Value]) -> Value {
+    match numbers(args) {
+        Some(ns) => Value::Literal(Literal::Number(ns.into_iter().sum())),
+        None => Value::Literal(Literal::Nil),
+    }
+}
+
+fn is_empty(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Literal(Literal::String(s))) => Value::Literal(if s.is_empty() {
+            Literal::True
+        } else {
+            Literal::False
+        }),
+        _ => Value::Literal(Literal::Nil),
+    }
+}
+
+/// Registers every stdlib builtin on `inter`, called once from
+/// `Interpreter::default` the same way the REPL and file runner both pick
+/// up `clock`/`input`/`len`/`str`/`num` for free. All but `is_empty` are
+/// also exposed under the `math` module, so a script can write `use math::sqrt;` instead of
+/// relying on these living in the global namespace.
+pub fn load(inter: &mut Interpreter) {
+    inter.register_native("sqrt", 1, sqrt);
+    inter.register_native("floor", 1, floor);
+    inter.register_native("ceil", 1, ceil);
+    inter.register_native("abs", 1, abs);
+    inter.register_native("pow", 2, pow);
+    inter.register_native("min", VARIADIC, min);
+    inter.register_native("max", VARIADIC, max);
+    inter.register_native("sum", VARIADIC, sum);
+    inter.register_native("is_empty", 1, is_empty);
+    inter.register_module(
+        "math",
+        &[
+            ("sqrt", 1, sqrt),
+            ("floor", 1, floor),
+            ("ceil", 1, ceil),
+            ("abs", 1, abs),
+            ("pow", 2, pow),
+            ("min", VARIADIC, min),
+            ("max", VARIADIC, max),
+            ("sum", VARIADIC, sum),
+        ],
+    );
+}
@@ -1,4 +1,5 @@
-use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
 
 use crate::tokens::{Token, TokenType};
 
@@ -17,31 +18,141 @@ impl From<&TokenType> for UnaryOperator {
         }
     }
 }
+
+impl std::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Minus => "-",
+                Self::Bang => "!",
+            }
+        )
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
-pub enum Literal<'a> {
-    String(&'a str),
+pub enum Literal {
+    String(Rc<str>),
     Number(f64),
+    /// Real/imaginary pair for an `Ni` literal. We don't depend on
+    /// `num-complex` here, just a plain pair: the interpreter only ever
+    /// adds, subtracts, multiplies, and divides these.
+    Complex(f64, f64),
     False,
     True,
     Nil,
 }
-impl<'a> From<Token<'a>> for Literal<'a> {
-    fn from(value: Token<'a>) -> Self {
-        match value.kind() {
+/// A `Number` token whose lexeme the scanner admitted but that still
+/// fails to parse as a number, e.g. a hex literal too large for an `i64`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidNumber(pub String);
+
+impl std::fmt::Display for InvalidNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not a valid number", self.0)
+    }
+}
+
+impl TryFrom<Token> for Literal {
+    type Error = InvalidNumber;
+
+    fn try_from(value: Token) -> Result<Self, Self::Error> {
+        Ok(match value.kind() {
             TokenType::False => Self::False,
             TokenType::True => Self::True,
             TokenType::Nil => Self::Nil,
-            TokenType::String => Self::String(value.value()),
-            TokenType::Number => Self::Number(value.value().parse().unwrap()),
+            TokenType::String => Self::String(value.lexeme()),
+            TokenType::Number => {
+                // Digit grouping (`1_000_000`) is a lexical nicety only;
+                // strip it before handing the rest to the real parser.
+                let digits = value.value().replace('_', "");
+                let invalid = || InvalidNumber(value.value().to_string());
+                // `0x`/`0o`/`0b` are integer-only radix prefixes, parsed
+                // separately since `f64::parse` has no notion of them;
+                // plain decimal (with an optional `e`/`E` exponent, which
+                // `f64::parse` already understands) falls through below.
+                let radix = if let Some(rest) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                    Some((rest, 16))
+                } else if let Some(rest) = digits.strip_prefix("0o").or_else(|| digits.strip_prefix("0O")) {
+                    Some((rest, 8))
+                } else {
+                    digits
+                        .strip_prefix("0b")
+                        .or_else(|| digits.strip_prefix("0B"))
+                        .map(|rest| (rest, 2))
+                };
+                match radix {
+                    Some((rest, radix)) => {
+                        Self::Number(i64::from_str_radix(rest, radix).map_err(|_| invalid())? as f64)
+                    }
+                    None => match digits.strip_suffix('i') {
+                        Some(imaginary) => {
+                            Self::Complex(0.0, imaginary.parse().map_err(|_| invalid())?)
+                        }
+                        None => Self::Number(digits.parse().map_err(|_| invalid())?),
+                    },
+                }
+            }
             _ => todo!(),
+        })
+    }
+}
+impl Literal {
+    /// `nil` and `false` are falsy, everything else (including `0`) is
+    /// truthy, matching the `and`/`or` short-circuit rules.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Self::Nil | Self::False)
+    }
+}
+
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{s}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Complex(re, im) => write!(f, "{re}+{im}i"),
+            Self::False => write!(f, "false"),
+            Self::True => write!(f, "true"),
+            Self::Nil => write!(f, "nil"),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum LogicalOperator {
+    And,
+    Or,
+}
+impl From<&TokenType> for LogicalOperator {
+    fn from(value: &TokenType) -> Self {
+        match value {
+            TokenType::And => Self::And,
+            TokenType::Or => Self::Or,
+            _ => todo!(),
+        }
+    }
+}
+
+impl std::fmt::Display for LogicalOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::And => "and",
+                Self::Or => "or",
+            }
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum BinaryOperator {
     Slash,
     Star,
+    Modulo,
+    Caret,
     Plus,
     Minus,
     Greater,
@@ -50,6 +161,8 @@ pub enum BinaryOperator {
     LessEqual,
     BangEqual,
     EqualEqual,
+    BitAnd,
+    BitOr,
 }
 impl From<&TokenType> for BinaryOperator {
     fn from(value: &TokenType) -> Self {
@@ -58,39 +171,467 @@ impl From<&TokenType> for BinaryOperator {
             TokenType::EqualEqual => Self::EqualEqual,
             TokenType::BangEqual => Self::BangEqual,
             TokenType::Slash => Self::Slash,
+            TokenType::Percent => Self::Modulo,
+            TokenType::Caret => Self::Caret,
             TokenType::Plus => Self::Plus,
             TokenType::Minus => Self::Minus,
             TokenType::Greater => Self::Greater,
             TokenType::GreaterEqual => Self::GreaterEqual,
             TokenType::Less => Self::Less,
             TokenType::LessEqual => Self::LessEqual,
+            TokenType::Ampersand => Self::BitAnd,
+            TokenType::Bar => Self::BitOr,
             _ => todo!(),
         }
     }
 }
 
+impl std::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Slash => "/",
+                Self::Star => "*",
+                Self::Modulo => "%",
+                Self::Caret => "^",
+                Self::Plus => "+",
+                Self::Minus => "-",
+                Self::Greater => ">",
+                Self::GreaterEqual => ">=",
+                Self::Less => "<",
+                Self::LessEqual => "<=",
+                Self::BangEqual => "!=",
+                Self::EqualEqual => "==",
+                Self::BitAnd => "&",
+                Self::BitOr => "|",
+            }
+        )
+    }
+}
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expr<'a> {
-    Literal(Literal<'a>),
-    Grouping(Box<Expr<'a>>),
-    Unary(UnaryOperator, Box<Expr<'a>>),
-    Binary(Box<Expr<'a>>, BinaryOperator, Box<Expr<'a>>),
+pub enum Expr {
+    Literal(Literal),
+    Grouping(Box<Expr>),
+    /// The `u64` is the source line of the operator, so a runtime error
+    /// raised while evaluating this node can report where it happened.
+    Unary(UnaryOperator, Box<Expr>, u64),
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>, u64),
+    /// The resolver fills in the `Cell` with the number of enclosing
+    /// scopes to hop before looking the name up; `None` means "unresolved
+    /// locally, treat as global".
+    Variable(Token, Cell<Option<usize>>),
+    /// `name = value`; resolved against the environment chain exactly
+    /// like `Variable`, via the same kind of resolver-filled hop count.
+    Assign(Token, Box<Expr>, Cell<Option<usize>>),
+    Call(Box<Expr>, Vec<Expr>, u64),
+    /// `and`/`or`; kept distinct from `Binary` so the interpreter can
+    /// short-circuit instead of evaluating both operands up front.
+    Logical(Box<Expr>, LogicalOperator, Box<Expr>, u64),
+    /// An anonymous function literal (`x -> x^2`), evaluating to a
+    /// `Value::Function` that closes over the defining environment just
+    /// like a named `Stmt::Function`.
+    Lambda(Vec<Token>, Vec<Stmt>, u64),
+    /// `target[index]`, e.g. `"flavien"[0]`.
+    Index(Box<Expr>, Box<Expr>, u64),
+    /// Postfix `n!`. Kept separate from `Unary` since it's the only
+    /// postfix operator and binds tighter than any prefix one.
+    Factorial(Box<Expr>, u64),
+    /// `object.field`. Parses today (there's no class/instance value to
+    /// actually hold a field yet, so evaluating one is a `TypeError`),
+    /// but gives the grammar and resolver the shape a future object
+    /// system can build on without another AST change.
+    Get(Box<Expr>, Token),
+    /// `object.field = value`; the `Get`-shaped counterpart to `Assign`,
+    /// produced the same way - parse the left side as a normal
+    /// expression, then reinterpret it as an assignment target once `=`
+    /// shows up.
+    Set(Box<Expr>, Token, Box<Expr>),
 }
 
-impl<'a> Expr<'a> {
-    pub fn binary(expr: Expr<'a>, op: BinaryOperator, right: Expr<'a>) -> Self {
-        Self::Binary(Box::new(expr), op, Box::new(right))
+impl Expr {
+    pub fn binary(expr: Expr, op: BinaryOperator, right: Expr, line: u64) -> Self {
+        Self::Binary(Box::new(expr), op, Box::new(right), line)
     }
 
-    pub fn unary(op: UnaryOperator, expr: Expr<'a>) -> Self {
-        Self::Unary(op, Box::new(expr))
+    pub fn logical(expr: Expr, op: LogicalOperator, right: Expr, line: u64) -> Self {
+        Self::Logical(Box::new(expr), op, Box::new(right), line)
     }
 
-    pub fn grouping(expr: Expr<'a>) -> Self {
+    pub fn unary(op: UnaryOperator, expr: Expr, line: u64) -> Self {
+        Self::Unary(op, Box::new(expr), line)
+    }
+
+    pub fn grouping(expr: Expr) -> Self {
         Self::Grouping(Box::new(expr))
     }
-    pub fn literal(expr: Literal<'a>) -> Self {
+    pub fn literal(expr: Literal) -> Self {
         Self::Literal(expr)
     }
+    pub fn variable(token: Token) -> Self {
+        Self::Variable(token, Cell::new(None))
+    }
+
+    pub fn assign(name: Token, value: Expr) -> Self {
+        Self::Assign(name, Box::new(value), Cell::new(None))
+    }
+
+    /// The name a `Variable` expression refers to, used by the resolver
+    /// to key its scope-stack lookups.
+    pub fn variable_name(&self) -> Option<&str> {
+        match self {
+            Self::Variable(token, _) => Some(token.value()),
+            _ => None,
+        }
+    }
+    pub fn call(callee: Expr, arguments: Vec<Expr>, line: u64) -> Self {
+        Self::Call(Box::new(callee), arguments, line)
+    }
+
+    pub fn lambda(params: Vec<Token>, body: Vec<Stmt>, line: u64) -> Self {
+        Self::Lambda(params, body, line)
+    }
+
+    pub fn index(target: Expr, index: Expr, line: u64) -> Self {
+        Self::Index(Box::new(target), Box::new(index), line)
+    }
+
+    pub fn factorial(expr: Expr, line: u64) -> Self {
+        Self::Factorial(Box::new(expr), line)
+    }
+
+    pub fn get(object: Expr, name: Token) -> Self {
+        Self::Get(Box::new(object), name)
+    }
+
+    pub fn set(object: Expr, name: Token, value: Expr) -> Self {
+        Self::Set(Box::new(object), name, Box::new(value))
+    }
+
+    /// Best-effort source line for this node, used to tag runtime errors;
+    /// literals and groupings carry no line of their own, so they fall
+    /// back to the line of whatever is nested inside them.
+    pub fn line(&self) -> u64 {
+        match self {
+            Self::Literal(_) => 0,
+            Self::Grouping(inner) => inner.line(),
+            Self::Unary(_, _, line)
+            | Self::Binary(_, _, _, line)
+            | Self::Call(_, _, line)
+            | Self::Logical(_, _, _, line)
+            | Self::Lambda(_, _, line)
+            | Self::Index(_, _, line)
+            | Self::Factorial(_, line) => *line,
+            Self::Variable(token, _) | Self::Assign(token, _, _) => token.line(),
+            Self::Get(_, name) | Self::Set(_, name, _) => name.line(),
+        }
+    }
+}
+
+/// Renders an `Expr` as a fully-parenthesized S-expression, e.g.
+/// `(* (- 1) (group (+ 2 3)))`. Meant for debugging a parse, not for
+/// round-tripping back into source.
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(literal) => write!(f, "{literal}"),
+            Self::Grouping(inner) => write!(f, "(group {inner})"),
+            Self::Unary(op, inner, _) => write!(f, "({op} {inner})"),
+            Self::Binary(left, op, right, _) => write!(f, "({op} {left} {right})"),
+            Self::Variable(token, _) => write!(f, "{}", token.value()),
+            Self::Assign(token, value, _) => write!(f, "(= {} {value})", token.value()),
+            Self::Call(callee, arguments, _) => {
+                write!(f, "(call {callee}")?;
+                for argument in arguments {
+                    write!(f, " {argument}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Logical(left, op, right, _) => write!(f, "({op} {left} {right})"),
+            Self::Lambda(params, body, _) => {
+                write!(f, "(lambda (")?;
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", param.value())?;
+                }
+                write!(f, ")")?;
+                for stmt in body {
+                    write!(f, " {stmt}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Index(target, index, _) => write!(f, "(index {target} {index})"),
+            Self::Factorial(inner, _) => write!(f, "(! {inner})"),
+            Self::Get(object, name) => write!(f, "(get {object} {})", name.value()),
+            Self::Set(object, name, value) => write!(f, "(set {object} {} {value})", name.value()),
+        }
+    }
+}
+
+/// Renders `expr` as Reverse Polish Notation tokens via a post-order walk,
+/// e.g. `(5+3)*2-1` becomes `["5", "3", "+", "2", "*", "1", "-"]`. A
+/// debugging/teaching view of the precedence and associativity the
+/// parser already baked into the tree, not a second grammar - it reads
+/// the same `Expr` the evaluator does, so it can't drift out of sync
+/// with what actually gets computed.
+pub fn to_postfix(expr: &Expr) -> Vec<String> {
+    let mut out = Vec::new();
+    postfix_into(expr, &mut out);
+    out
+}
+
+fn postfix_into(expr: &Expr, out: &mut Vec<String>) {
+    match expr {
+        Expr::Literal(literal) => out.push(literal.to_string()),
+        Expr::Grouping(inner) => postfix_into(inner, out),
+        Expr::Unary(op, inner, _) => {
+            postfix_into(inner, out);
+            out.push(op.to_string());
+        }
+        Expr::Binary(left, op, right, _) => {
+            postfix_into(left, out);
+            postfix_into(right, out);
+            out.push(op.to_string());
+        }
+        Expr::Logical(left, op, right, _) => {
+            postfix_into(left, out);
+            postfix_into(right, out);
+            out.push(op.to_string());
+        }
+        Expr::Variable(token, _) => out.push(token.value().to_string()),
+        Expr::Assign(token, value, _) => {
+            postfix_into(value, out);
+            out.push(format!("{}=", token.value()));
+        }
+        Expr::Call(callee, arguments, _) => {
+            for argument in arguments {
+                postfix_into(argument, out);
+            }
+            postfix_into(callee, out);
+            out.push("call".to_string());
+        }
+        Expr::Lambda(..) => out.push("<lambda>".to_string()),
+        Expr::Index(target, index, _) => {
+            postfix_into(target, out);
+            postfix_into(index, out);
+            out.push("[]".to_string());
+        }
+        Expr::Factorial(inner, _) => {
+            postfix_into(inner, out);
+            out.push("!".to_string());
+        }
+        Expr::Get(object, name) => {
+            postfix_into(object, out);
+            out.push(format!(".{}", name.value()));
+        }
+        Expr::Set(object, name, value) => {
+            postfix_into(object, out);
+            postfix_into(value, out);
+            out.push(format!(".{}=", name.value()));
+        }
+    }
+}
+
+/// A statement in the language: either produces a side effect (`Print`,
+/// `Expression`) or introduces a new binding (`Var`, `Function`).
+#[derive(Debug, PartialEq, Clone)]
+pub enum Stmt {
+    Expression(Expr),
+    Print(Expr),
+    Var(Rc<str>, Option<Expr>),
+    Function(Token, Vec<Token>, Vec<Stmt>),
+    Block(Vec<Stmt>),
+    /// `None` is a bare `return;`, equivalent to returning `nil`.
+    Return(Option<Expr>),
+    /// `if` condition, then-branch, optional else-branch.
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    /// `loop { ... }`; runs forever, relying on a `return` to unwind out.
+    Loop(Box<Stmt>),
+    /// `do { ... } while (condition);`; runs the body once up front, then
+    /// keeps re-running it for as long as `condition` stays truthy.
+    DoWhile(Expr, Box<Stmt>),
+    /// `use a::b;`, a path of `::`-separated segments identifying a
+    /// module and, for a two-segment path, a single name exported from
+    /// it. A one-segment path (`use math;`) imports every name the module
+    /// exports instead of just one; a longer path parses, but the
+    /// interpreter reports it as an unknown module. The `u64` is the
+    /// source line, for reporting an unknown module or export.
+    Use(Vec<Rc<str>>, u64),
+}
+
+/// Renders a `Stmt` as a fully-parenthesized S-expression, e.g.
+/// `(var x = 1)`. Meant for debugging a parse, not for round-tripping
+/// back into source.
+impl std::fmt::Display for Stmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Expression(expr) => write!(f, "{expr}"),
+            Self::Print(expr) => write!(f, "(print {expr})"),
+            Self::Var(name, Some(initializer)) => write!(f, "(var {name} = {initializer})"),
+            Self::Var(name, None) => write!(f, "(var {name})"),
+            Self::Function(name, params, body) => {
+                write!(f, "(fun {} (", name.value())?;
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", param.value())?;
+                }
+                write!(f, ")")?;
+                for stmt in body {
+                    write!(f, " {stmt}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Block(stmts) => {
+                write!(f, "(block")?;
+                for stmt in stmts {
+                    write!(f, " {stmt}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Return(Some(expr)) => write!(f, "(return {expr})"),
+            Self::Return(None) => write!(f, "(return)"),
+            Self::If(condition, then_branch, Some(else_branch)) => {
+                write!(f, "(if {condition} {then_branch} {else_branch})")
+            }
+            Self::If(condition, then_branch, None) => write!(f, "(if {condition} {then_branch})"),
+            Self::While(condition, body) => write!(f, "(while {condition} {body})"),
+            Self::Loop(body) => write!(f, "(loop {body})"),
+            Self::DoWhile(condition, body) => write!(f, "(do-while {condition} {body})"),
+            Self::Use(path, _) => {
+                write!(f, "(use {})", path.join("::"))
+            }
+        }
+    }
+}
+
+/// Folds constant sub-expressions bottom-up, before interpretation, so
+/// e.g. `1 + 2 * 3` collapses to a single `Literal::Number(7.0)` instead
+/// of re-doing that arithmetic on every evaluation. `Variable` and
+/// `Assign` targets are left untouched since their values are only known
+/// at runtime, and `Slash`/`Modulo` are never folded when the divisor is
+/// exactly `0.0`, so the runtime's own division-by-zero handling still
+/// applies to those nodes.
+pub fn optimize(expr: Expr) -> Expr {
+    match expr {
+        Expr::Grouping(inner) => match optimize(*inner) {
+            folded @ Expr::Literal(_) => folded,
+            folded => Expr::grouping(folded),
+        },
+        Expr::Unary(op, inner, line) => {
+            let inner = optimize(*inner);
+            match (&op, &inner) {
+                (UnaryOperator::Minus, Expr::Literal(Literal::Number(n))) => {
+                    Expr::literal(Literal::Number(-n))
+                }
+                (UnaryOperator::Bang, Expr::Literal(literal)) => Expr::literal(bool_literal(!literal.is_truthy())),
+                _ => Expr::unary(op, inner, line),
+            }
+        }
+        Expr::Binary(left, op, right, line) => {
+            let left = optimize(*left);
+            let right = optimize(*right);
+            match fold_binary(&left, &op, &right) {
+                Some(folded) => folded,
+                None => Expr::binary(left, op, right, line),
+            }
+        }
+        Expr::Logical(left, op, right, line) => {
+            Expr::logical(optimize(*left), op, optimize(*right), line)
+        }
+        Expr::Call(callee, arguments, line) => Expr::call(
+            optimize(*callee),
+            arguments.into_iter().map(optimize).collect(),
+            line,
+        ),
+        Expr::Lambda(params, body, line) => Expr::lambda(params, optimize_stmts(body), line),
+        Expr::Index(target, index, line) => Expr::index(optimize(*target), optimize(*index), line),
+        Expr::Factorial(inner, line) => Expr::factorial(optimize(*inner), line),
+        Expr::Assign(name, value, depth) => Expr::Assign(name, Box::new(optimize(*value)), depth),
+        Expr::Get(object, name) => Expr::get(optimize(*object), name),
+        Expr::Set(object, name, value) => Expr::set(optimize(*object), name, optimize(*value)),
+        literal @ Expr::Literal(_) | literal @ Expr::Variable(..) => literal,
+    }
+}
+
+/// Evaluates `left op right` when both sides have already folded down to
+/// literals, or returns `None` to leave the node as-is.
+fn fold_binary(left: &Expr, op: &BinaryOperator, right: &Expr) -> Option<Expr> {
+    if let (
+        Expr::Literal(Literal::String(l)),
+        BinaryOperator::Plus,
+        Expr::Literal(Literal::String(r)),
+    ) = (left, op, right)
+    {
+        return Some(Expr::literal(Literal::String(Rc::from(format!("{l}{r}")))));
+    }
+    let (Expr::Literal(Literal::Number(l)), Expr::Literal(Literal::Number(r))) = (left, right) else {
+        return None;
+    };
+    let (l, r) = (*l, *r);
+    let literal = match op {
+        BinaryOperator::Plus => Literal::Number(l + r),
+        BinaryOperator::Minus => Literal::Number(l - r),
+        BinaryOperator::Star => Literal::Number(l * r),
+        BinaryOperator::Slash if r != 0.0 => Literal::Number(l / r),
+        BinaryOperator::Slash => return None,
+        BinaryOperator::Modulo if r != 0.0 => Literal::Number(l % r),
+        BinaryOperator::Modulo => return None,
+        BinaryOperator::Caret => Literal::Number(l.powf(r)),
+        BinaryOperator::Greater => bool_literal(l > r),
+        BinaryOperator::GreaterEqual => bool_literal(l >= r),
+        BinaryOperator::Less => bool_literal(l < r),
+        BinaryOperator::LessEqual => bool_literal(l <= r),
+        BinaryOperator::EqualEqual => bool_literal(l == r),
+        BinaryOperator::BangEqual => bool_literal(l != r),
+        BinaryOperator::BitAnd => Literal::Number(((l as i64) & (r as i64)) as f64),
+        BinaryOperator::BitOr => Literal::Number(((l as i64) | (r as i64)) as f64),
+    };
+    Some(Expr::literal(literal))
+}
+
+fn bool_literal(b: bool) -> Literal {
+    if b {
+        Literal::True
+    } else {
+        Literal::False
+    }
+}
+
+/// Applies [`optimize`] to every expression reachable from `stmt`,
+/// recursing into nested blocks and bodies so a whole parsed program
+/// gets the same constant folding as a lone expression.
+pub fn optimize_stmt(stmt: Stmt) -> Stmt {
+    match stmt {
+        Stmt::Expression(expr) => Stmt::Expression(optimize(expr)),
+        Stmt::Print(expr) => Stmt::Print(optimize(expr)),
+        Stmt::Var(name, initializer) => Stmt::Var(name, initializer.map(optimize)),
+        Stmt::Function(name, params, body) => Stmt::Function(name, params, optimize_stmts(body)),
+        Stmt::Block(stmts) => Stmt::Block(optimize_stmts(stmts)),
+        Stmt::Return(expr) => Stmt::Return(expr.map(optimize)),
+        Stmt::If(condition, then_branch, else_branch) => Stmt::If(
+            optimize(condition),
+            Box::new(optimize_stmt(*then_branch)),
+            else_branch.map(|branch| Box::new(optimize_stmt(*branch))),
+        ),
+        Stmt::While(condition, body) => {
+            Stmt::While(optimize(condition), Box::new(optimize_stmt(*body)))
+        }
+        Stmt::Loop(body) => Stmt::Loop(Box::new(optimize_stmt(*body))),
+        Stmt::DoWhile(condition, body) => {
+            Stmt::DoWhile(optimize(condition), Box::new(optimize_stmt(*body)))
+        }
+        Stmt::Use(path, line) => Stmt::Use(path, line),
+    }
+}
+
+/// Applies [`optimize_stmt`] to a whole parsed program.
+pub fn optimize_stmts(stmts: Vec<Stmt>) -> Vec<Stmt> {
+    stmts.into_iter().map(optimize_stmt).collect()
 }
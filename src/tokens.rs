@@ -1,18 +1,25 @@
 use std::fmt::Display;
+use std::rc::Rc;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    /// `::`, separating a module path's segments in a `use` statement.
+    ColonColon,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Caret,
     Bang,
     Equal,
     Greater,
@@ -22,6 +29,17 @@ pub enum TokenType {
     EqualEqual,
     GreaterEqual,
     LessEqual,
+    /// `->`, introducing a lambda's body.
+    Arrow,
+    /// `|>`, the pipe operator: `a |> f` desugars to `f(a)`.
+    Pipe,
+    Ampersand,
+    /// `|`, bitwise or. Distinct from `Pipe` (`|>`), which needs the
+    /// trailing `>` to lex.
+    Bar,
+    /// `\`, boxing the binary operator that follows it into a two-argument
+    /// callable, e.g. `\+` is `fun(x, y) { return x + y; }`.
+    Backslash,
 
     Identifier,
     Number,
@@ -34,50 +52,69 @@ pub enum TokenType {
     Fun,
     For,
     If,
+    Loop,
+    Do,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Use,
     Var,
     While,
     Eof,
 }
 
-#[derive(Debug)]
-pub struct Token<'a> {
+/// A lexeme is stored behind an `Rc<str>` rather than borrowed from the
+/// source buffer, so a `Token` (and everything built on top of it, up to
+/// a whole parsed `Stmt` tree) can outlive the line of source it came
+/// from — the REPL needs this to keep accumulating definitions across
+/// separate `readline` calls instead of discarding each line's tree once
+/// its buffer goes away. Repeated lexemes (a variable referenced many
+/// times, a keyword) just clone the `Rc`, not the text.
+#[derive(Debug, Clone)]
+pub struct Token {
     kind: TokenType,
-    lexem: &'a str,
+    lexem: Rc<str>,
     line: u64,
 }
 
-impl<'a> PartialEq for Token<'a> {
+impl PartialEq for Token {
     fn eq(&self, other: &Self) -> bool {
         self.kind == other.kind && self.lexem == other.lexem && self.line == other.line
     }
 }
 
-impl<'a> Token<'a> {
-    pub fn new(kind: TokenType, lexem: &'a str, line: u64) -> Self {
-        Self { kind, lexem, line }
+impl Token {
+    pub fn new(kind: TokenType, lexem: impl Into<Rc<str>>, line: u64) -> Self {
+        Self { kind, lexem: lexem.into(), line }
     }
     pub fn eof(line: u64) -> Self {
         Self {
             kind: TokenType::Eof,
-            lexem: "",
+            lexem: Rc::from(""),
             line,
         }
     }
-    pub fn value(&self) -> &'a str {
+    pub fn value(&self) -> &str {
         &self.lexem
     }
+    /// A cheap clone of this token's lexeme, for callers (environments,
+    /// the interner) that need to own a name rather than borrow it for
+    /// the duration of a single call.
+    pub fn lexeme(&self) -> Rc<str> {
+        Rc::clone(&self.lexem)
+    }
     pub fn kind(&self) -> &TokenType {
         &self.kind
     }
+    pub fn line(&self) -> u64 {
+        self.line
+    }
 }
 
-impl<'a> Display for Token<'a> {
+impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,